@@ -0,0 +1,134 @@
+use animation_replace_roblox::StudioParser;
+use animation_replace_roblox::generator::PlaceGenerator;
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+use std::path::{Path, PathBuf};
+
+/// Place sizes every stage is measured at.
+fn places() -> Vec<(&'static str, PlaceGenerator)> {
+    vec![
+        ("small", PlaceGenerator::new()),
+        (
+            "large",
+            PlaceGenerator::new()
+                .animations(20_000)
+                .scripts(2_000)
+                .value_objects(2_000)
+                .distinct_ids(5_000),
+        ),
+    ]
+}
+
+/// Writes every place once and returns where, with the generator that built it.
+fn written_places() -> Vec<(&'static str, PathBuf, PlaceGenerator)> {
+    places()
+        .into_iter()
+        .map(|(name, generator)| {
+            let path = std::env::temp_dir().join(format!("animation-replace-bench-{}.rbxl", name));
+            generator.write(&path).unwrap();
+            (name, path, generator)
+        })
+        .collect()
+}
+
+fn load(path: &Path) -> StudioParser {
+    StudioParser::builder().file_path(path).build().unwrap()
+}
+
+fn bench_generate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate");
+    group.sample_size(10);
+    for (name, generator) in places() {
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| generator.generate())
+        });
+    }
+    group.finish();
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(10);
+    for (name, path, _) in written_places() {
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| load(black_box(&path)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scan");
+    group.sample_size(10);
+    for (name, path, _) in written_places() {
+        let parser = load(&path);
+        group.bench_function(BenchmarkId::new("animation_ids", name), |b| {
+            b.iter(|| parser.animation_ids())
+        });
+        group.bench_function(BenchmarkId::new("script_asset_ids", name), |b| {
+            b.iter(|| parser.script_asset_ids())
+        });
+        group.bench_function(BenchmarkId::new("asset_inventory", name), |b| {
+            b.iter(|| parser.asset_inventory())
+        });
+    }
+    group.finish();
+}
+
+fn bench_apply(c: &mut Criterion) {
+    let mut group = c.benchmark_group("apply");
+    group.sample_size(10);
+    for (name, path, generator) in written_places() {
+        let mapping = generator.mapping();
+        group.bench_function(BenchmarkId::new("update_game_animations", name), |b| {
+            b.iter_batched(
+                || load(&path),
+                |mut parser| parser.update_game_animations(black_box(&mapping)),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("update_script_animations", name), |b| {
+            b.iter_batched(
+                || load(&path),
+                |mut parser| {
+                    parser
+                        .update_script_animations(black_box(&mapping))
+                        .unwrap()
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_save(c: &mut Criterion) {
+    let mut group = c.benchmark_group("save");
+    group.sample_size(10);
+    for (name, path, generator) in written_places() {
+        let mapping = generator.mapping();
+        let mut parser = load(&path);
+        parser.update_game_animations(&mapping);
+        parser.update_script_animations(&mapping).unwrap();
+
+        let output =
+            std::env::temp_dir().join(format!("animation-replace-bench-{}-out.rbxl", name));
+        group.bench_function(BenchmarkId::new("save_to_rbxl", name), |b| {
+            b.iter(|| parser.save_to_rbxl(&output).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("save_verified", name), |b| {
+            b.iter(|| parser.save_verified(&output, Some(&mapping)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_generate,
+    bench_parse,
+    bench_scan,
+    bench_apply,
+    bench_save
+);
+criterion_main!(benches);
@@ -0,0 +1,80 @@
+use animation_replace_roblox::AnimationFilter;
+use animation_replace_roblox::StudioParser;
+use animation_replace_roblox::generator::PlaceGenerator;
+use animation_replace_roblox::script::script_editor::IdReplacer;
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use rbx_types::Variant;
+use std::hint::black_box;
+use ustr::Ustr;
+
+/// Thousands of long scripts, each with a reference every ten lines.
+fn large_place() -> PlaceGenerator {
+    PlaceGenerator::new()
+        .animations(0)
+        .value_objects(0)
+        .scripts(5_000)
+        .script_lines(300)
+        .references_per_script(30)
+        .distinct_ids(2_000)
+}
+
+/// The first script's source, to rewrite on its own.
+fn first_source(generator: &PlaceGenerator) -> String {
+    let dom = generator.generate();
+    dom.descendants()
+        .find(|instance| instance.class.as_str() == "ModuleScript")
+        .and_then(
+            |instance| match instance.properties.get(&Ustr::from("Source")) {
+                Some(Variant::String(source)) => Some(source.clone()),
+                _ => None,
+            },
+        )
+        .unwrap()
+}
+
+fn bench_replace(c: &mut Criterion) {
+    let generator = large_place().scripts(1);
+    let mapping = generator.mapping();
+    let filter = AnimationFilter::new();
+    let mapped = first_source(&generator);
+    let unmapped = first_source(&generator.clone().references_per_script(0));
+
+    let mut group = c.benchmark_group("replace_ids");
+    group.bench_function("build_replacer", |b| {
+        b.iter(|| IdReplacer::new(black_box(&mapping), &filter).unwrap())
+    });
+
+    let replacer = IdReplacer::new(&mapping, &filter).unwrap();
+    group.bench_function("mapped_source", |b| {
+        b.iter(|| replacer.replace(black_box(&mapped)))
+    });
+    group.bench_function("unmapped_source", |b| {
+        b.iter(|| replacer.replace(black_box(&unmapped)))
+    });
+    group.finish();
+}
+
+fn bench_large_place(c: &mut Criterion) {
+    let generator = large_place();
+    let mapping = generator.mapping();
+    let path = std::env::temp_dir().join("animation-replace-bench-scripts.rbxl");
+    generator.write(&path).unwrap();
+
+    let mut group = c.benchmark_group("large_place");
+    group.sample_size(10);
+    group.bench_function("update_script_animations", |b| {
+        b.iter_batched(
+            || StudioParser::builder().file_path(&path).build().unwrap(),
+            |mut parser| {
+                parser
+                    .update_script_animations(black_box(&mapping))
+                    .unwrap()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_replace, bench_large_place);
+criterion_main!(benches);
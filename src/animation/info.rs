@@ -1,32 +1,32 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
 
 use crate::AnimationUploader;
+use crate::Creator;
+use crate::places::MAX_PLACE_CANDIDATES;
+use crate::rate_limit::ApiFamily;
+use log::debug;
 use roboat::ClientBuilder;
 
 impl AnimationUploader {
-    /// Fetches a place_id for an item owner
-    pub async fn place_id(
-        &self,
-        asset_id: u64,
-        cached_places: &mut HashMap<u64, Vec<u64>>, // place_id -> asset_ids
-    ) -> anyhow::Result<u64> {
+    /// Looks up the user or group that created an asset.
+    pub async fn asset_creator(&self, asset_id: u64) -> anyhow::Result<Creator> {
         let client = ClientBuilder::new()
-            .roblosecurity(self.roblosecurity.to_string())
+            .roblosecurity(self.roblosecurity.expose().to_string())
             .build();
 
-        let asset_info = client.get_asset_info(asset_id).await?;
+        let client = &client;
+        let asset_info = self
+            .call_api(ApiFamily::AssetInfo, move || async move {
+                Ok(client.get_asset_info(asset_id).await?)
+            })
+            .await?;
 
         if let Some(user_id) = asset_info.creation_context.creator.user_id {
             let user_id_parsed = user_id
                 .parse::<u64>()
                 .map_err(|e| anyhow::anyhow!("Failed to parse user_id '{}': {}", user_id, e))?;
 
-            let place_id = self.user_places(user_id_parsed).await?;
-
-            // record mapping place_id -> asset_id
-            cached_places.entry(place_id).or_default().push(asset_id);
-
-            return Ok(place_id);
+            return Ok(Creator::User(user_id_parsed));
         }
 
         if let Some(group_id) = asset_info.creation_context.creator.group_id {
@@ -34,12 +34,7 @@ impl AnimationUploader {
                 .parse::<u64>()
                 .map_err(|e| anyhow::anyhow!("Failed to parse group_id '{}': {}", group_id, e))?;
 
-            let place_id = self.group_places(group_id_parsed).await?;
-
-            // record mapping place_id -> asset_id
-            cached_places.entry(place_id).or_default().push(asset_id);
-
-            return Ok(place_id);
+            return Ok(Creator::Group(group_id_parsed));
         }
 
         Err(anyhow::anyhow!(
@@ -47,9 +42,53 @@ impl AnimationUploader {
             asset_id
         ))
     }
+
+    /// Fetches a place_id for an item owner: its override or cached place when known,
+    /// otherwise the first of its games.
+    pub async fn place_id(&self, asset_id: u64) -> anyhow::Result<u64> {
+        let creator = self.cached_asset_creator(asset_id).await?;
+        let known = self.place_resolver.known_places(creator).first().copied();
+        let place_id = match known {
+            Some(place_id) => place_id,
+            None => self
+                .discovered_places(creator)
+                .await?
+                .first()
+                .copied()
+                .ok_or_else(|| anyhow::anyhow!("Couldn't find a place for {}", creator))?,
+        };
+
+        debug!(asset_id = asset_id, place_id = place_id; "Found place for asset");
+        Ok(place_id)
+    }
+
+    /// Root places of a creator's games, listed once per run and capped at
+    /// `MAX_PLACE_CANDIDATES`. Empty when the creator has no public games.
+    ///
+    /// * Notes
+    /// Only successful listings are cached, so a failed request is tried again the next time
+    /// the creator comes up.
+    pub async fn discovered_places(&self, creator: Creator) -> anyhow::Result<Vec<u64>> {
+        if let Some(places) = self.place_resolver.cached_games(creator) {
+            return Ok(places);
+        }
+
+        let mut places = match creator {
+            Creator::User(user_id) => self.user_places(user_id).await?,
+            Creator::Group(group_id) => self.group_places(group_id).await?,
+        };
+        let mut seen = HashSet::new();
+        places.retain(|place_id| seen.insert(*place_id));
+        places.truncate(MAX_PLACE_CANDIDATES);
+
+        self.place_resolver.remember_games(creator, places.clone());
+        Ok(places)
+    }
 }
+
 mod internal {
     use crate::AnimationUploader;
+    use crate::rate_limit::{ApiFamily, check_rate_limited};
     use bytes::Bytes;
     use reqwest::header::{HeaderMap, HeaderValue};
     use roboat::{
@@ -59,34 +98,45 @@ mod internal {
     use tokio::time;
 
     impl AnimationUploader {
-        /// Function for roboat getting root_place id for user place
-        pub(super) async fn user_places(&self, user_id: u64) -> anyhow::Result<u64> {
+        /// Function for roboat getting the root_place ids of a user's games
+        pub(super) async fn user_places(&self, user_id: u64) -> anyhow::Result<Vec<u64>> {
             let client = ClientBuilder::new().build();
-            let games_response = client.user_games(user_id).await?;
-            if let Some(first_place) = games_response.data.first() {
-                Ok(first_place.root_place.id)
-            } else {
-                Err(anyhow::anyhow!("Couldn't find place for user {}", user_id))
-            }
+            let client = &client;
+            let games_response = self
+                .call_api(ApiFamily::Games, move || async move {
+                    Ok(client.user_games(user_id).await?)
+                })
+                .await?;
+            Ok(games_response
+                .data
+                .iter()
+                .map(|game| game.root_place.id)
+                .collect())
         }
 
-        /// Function for roboat getting root_place id for group place
-        pub(super) async fn group_places(&self, group_id: u64) -> anyhow::Result<u64> {
+        /// Function for roboat getting the root_place ids of a group's games
+        pub(super) async fn group_places(&self, group_id: u64) -> anyhow::Result<Vec<u64>> {
             let client = ClientBuilder::new().build();
-            let games_response = client.group_games(group_id).await?;
-            if let Some(first_place) = games_response.data.first() {
-                Ok(first_place.root_place.id)
-            } else {
-                Err(anyhow::anyhow!(
-                    "Couldn't find place for group {}",
-                    group_id
-                ))
-            }
+            let client = &client;
+            let games_response = self
+                .call_api(ApiFamily::Games, move || async move {
+                    Ok(client.group_games(group_id).await?)
+                })
+                .await?;
+            Ok(games_response
+                .data
+                .iter()
+                .map(|game| game.root_place.id)
+                .collect())
         }
 
         /// Checks asset metadata for up to 250 assets.
         /// Now it also returns a place made by the creator, for place_id header to upload
         /// animations
+        ///
+        /// * Notes
+        /// Goes through the batch metadata rate limiter, so 429s and timeouts are retried
+        /// as the uploader's retry policy allows.
         pub async fn check_asset_metadata(
             &self,
             asset_ids: Vec<AssetBatchPayload>,
@@ -106,55 +156,42 @@ mod internal {
                 .map_err(|e| RoboatError::ReqwestError(e))?;
 
             let client = ClientBuilder::new()
-                .roblosecurity(self.roblosecurity.clone())
+                .roblosecurity(self.roblosecurity.expose().to_string())
                 .reqwest_client(timeout_client)
                 .build();
 
-            match client.post_asset_metadata_batch(asset_ids).await {
-                Ok(x) => Ok(Some(x)),
-                Err(e) => Err(e.into()),
-            }
+            let client = &client;
+            let asset_ids = &asset_ids;
+            let responses = self
+                .call_api(ApiFamily::BatchMetadata, move || async move {
+                    Ok(client.post_asset_metadata_batch(asset_ids.clone()).await?)
+                })
+                .await?;
+
+            Ok(Some(responses))
         }
 
-        /// Downloads file bytes from a URL with retry logic.
+        /// Downloads file bytes from a URL, retried and timed out according to the
+        /// uploader's retry policy.
         ///
         /// # Examples
         ///
         /// ```rust
         /// let bytes = uploader.file_bytes_from_url("https://example.com/file.rbxm".to_string()).await?;
         /// ```
-        pub async fn file_bytes_from_url(&self, url: String) -> Result<Bytes, RoboatError> {
+        pub async fn file_bytes_from_url(&self, url: String) -> anyhow::Result<Bytes> {
             use reqwest::Client;
-            use tokio::time::{Duration, timeout};
-
-            let max_retries: usize = 3;
-            const TIMEOUT_SECS: u64 = 3;
 
             let client = Client::new();
 
-            for attempt in 1..=max_retries {
-                let result =
-                    timeout(Duration::from_secs(TIMEOUT_SECS), client.get(&url).send()).await;
-
-                match result {
-                    Ok(Ok(response)) => {
-                        let bytes = response.bytes().await.map_err(RoboatError::ReqwestError)?;
-                        return Ok(bytes);
-                    }
-                    Ok(Err(e)) => {
-                        if attempt == max_retries {
-                            return Err(RoboatError::ReqwestError(e))?;
-                        }
-                    }
-                    Err(e) => {
-                        println!("Getting btres from url error: {:?}", e);
-                        if attempt == max_retries {
-                            return Err(RoboatError::InternalServerError);
-                        }
-                    }
-                }
-            }
-            unreachable!("Loop should always return")
+            let client = &client;
+            let url = &url;
+            self.call_api(ApiFamily::Download, move || async move {
+                let response = client.get(url).send().await?;
+                let response = check_rate_limited(response)?.error_for_status()?;
+                Ok(response.bytes().await?)
+            })
+            .await
         }
     }
 }
@@ -1,25 +1,146 @@
 use crate::AnimationUploader;
 use crate::StudioParser;
+use crate::asset::AssetType;
+use crate::progress::{ProgressEvent, ScanSource};
+use crate::studio::animate_script::is_roblox_default_animation;
+use log::warn;
 use roboat::RoboatError;
 use roboat::assetdelivery::AssetBatchResponse;
+use std::collections::HashMap;
 
 pub mod info;
+pub mod pipeline;
+pub mod preflight;
+pub mod resolver;
 pub mod uploader;
 
 // Implement uploader code into the studio struct
 impl StudioParser {
     pub fn animation_uploader(&self) -> Result<AnimationUploader, RoboatError> {
         match &self.roblosecurity {
-            Some(cookie) => Ok(AnimationUploader::new(cookie.clone())),
+            Some(cookie) => {
+                let mut uploader = AnimationUploader::new(cookie.clone())
+                    .with_rate_limits(self.rate_limits.clone())
+                    .with_retry_policy(self.retry_policy.clone())
+                    .with_cancellation(self.cancellation.clone())
+                    .with_endpoints(self.endpoints.clone())
+                    .with_name_template(self.name_template.clone())
+                    .with_place_resolver(self.place_resolver.clone())
+                    .with_batch_concurrency(self.batch_concurrency);
+                if self.name_template.contains("{name}") {
+                    uploader = uploader.with_animation_names(self.animation_names());
+                }
+                uploader.progress = self.progress.clone();
+                Ok(uploader)
+            }
             None => Err(RoboatError::InvalidRoblosecurity),
         }
     }
 
+    /// Maps each animation ID to the name of the first Animation instance using it.
+    pub fn animation_names(&self) -> HashMap<String, String> {
+        let mut names = HashMap::new();
+        for reference in self.asset_references(AssetType::Animation) {
+            if let Some(instance) = self.dom.get_by_ref(reference.referent) {
+                names
+                    .entry(reference.asset_id.to_string())
+                    .or_insert_with(|| instance.name.clone());
+            }
+        }
+        names
+    }
+
+    /// Every animation ID in the place, from instances, values, attributes and scripts,
+    /// without fetching anything. Used to collect IDs across several places before one fetch.
+    pub fn animation_ids(&self) -> Vec<u64> {
+        let workspace_ids = self.asset_ids(AssetType::Animation);
+        self.progress.emit(ProgressEvent::ScanFound {
+            source: ScanSource::Workspace,
+            asset_type: AssetType::Animation,
+            count: workspace_ids.len(),
+        });
+
+        let script_ids = self.script_asset_ids();
+        self.progress.emit(ProgressEvent::ScanFound {
+            source: ScanSource::Scripts,
+            asset_type: AssetType::Animation,
+            count: script_ids.len(),
+        });
+
+        let mut asset_ids = workspace_ids;
+        asset_ids.extend(script_ids);
+        asset_ids.sort();
+        asset_ids.dedup();
+        asset_ids
+    }
+
     pub async fn fetch_animation_assets(
         &self,
         asset_ids: Vec<u64>,
     ) -> anyhow::Result<Vec<AssetBatchResponse>> {
-        let uploader = self.animation_uploader()?;
-        uploader.fetch_animation_assets(asset_ids).await
+        self.fetch_assets(asset_ids, AssetType::Animation).await
+    }
+
+    /// Fetches asset details for the IDs that pass the parser's filter, keeping only
+    /// responses of the requested asset type.
+    ///
+    /// * Notes
+    /// Creator rules need one asset info request per ID, so they are only checked when the
+    /// filter has any. Responses without an asset type are kept.
+    pub async fn fetch_assets(
+        &self,
+        asset_ids: Vec<u64>,
+        asset_type: AssetType,
+    ) -> anyhow::Result<Vec<AssetBatchResponse>> {
+        let asset_ids = self.reupload_candidates(asset_ids, asset_type).await?;
+        let responses = self
+            .animation_uploader()?
+            .fetch_animation_assets(asset_ids)
+            .await?;
+        Ok(responses
+            .into_iter()
+            .filter(|response| {
+                response
+                    .asset_type_id
+                    .is_none_or(|type_id| asset_type.asset_type_ids().contains(&(type_id as u64)))
+            })
+            .collect())
+    }
+
+    /// The IDs that pass the parser's filter and default animation exclusion, in the order
+    /// given, without fetching their details.
+    ///
+    /// * Notes
+    /// Creator rules need one asset info request per ID, so they are only checked when the
+    /// filter has any.
+    pub async fn reupload_candidates(
+        &self,
+        mut asset_ids: Vec<u64>,
+        asset_type: AssetType,
+    ) -> anyhow::Result<Vec<u64>> {
+        if asset_type == AssetType::Animation && self.exclude_default_animations {
+            asset_ids.retain(|asset_id| !is_roblox_default_animation(*asset_id));
+        }
+        asset_ids.retain(|asset_id| self.filter.allows_id(*asset_id));
+
+        if self.filter.has_creator_rules() {
+            let uploader = self.animation_uploader()?;
+            let mut allowed_ids = Vec::with_capacity(asset_ids.len());
+            for (asset_id, creator) in uploader.asset_creators(&asset_ids).await {
+                match creator {
+                    Ok(creator) if self.filter.allows_creator(creator) => {
+                        allowed_ids.push(asset_id)
+                    }
+                    Ok(_) => {}
+                    Err(_) if self.cancellation.is_cancelled() => {}
+                    Err(e) => {
+                        warn!(asset_id = asset_id; "Skipping asset: couldn't resolve its creator: {}", e);
+                    }
+                }
+            }
+            asset_ids = allowed_ids;
+        }
+
+        Ok(asset_ids)
     }
 }
@@ -0,0 +1,217 @@
+use crate::AnimationUploader;
+use crate::animation::uploader::BATCH_SIZE;
+use crate::asset::AssetType;
+use crate::mapping::MappingEntry;
+use crate::progress::ProgressEvent;
+use futures::StreamExt;
+use log::warn;
+use std::sync::Arc;
+use tokio::sync::{Mutex, mpsc};
+use tokio::task::JoinSet;
+
+/// Animations waiting for a download slot. Kept small so fetching stays only a little ahead
+/// of uploading.
+pub const PIPELINE_CAPACITY: usize = 64;
+
+/// Totals of a pipeline run.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineSummary {
+    /// Animations whose metadata came back with a download location.
+    pub queued: usize,
+    pub uploaded: usize,
+    /// Downloads, validations or uploads that failed after every retry.
+    pub failed: usize,
+    /// Queued but not started before cancellation.
+    pub abandoned: usize,
+}
+
+impl PipelineSummary {
+    fn add(&mut self, other: PipelineSummary) {
+        self.uploaded += other.uploaded;
+        self.failed += other.failed;
+        self.abandoned += other.abandoned;
+    }
+}
+
+/// Checks a download looks like a Roblox model (binary `<roblox!` or XML `<roblox `)
+/// before it is uploaded, so error pages and empty bodies aren't published as animations.
+pub fn validate_animation_file(file: &[u8]) -> anyhow::Result<()> {
+    if file.is_empty() {
+        return Err(anyhow::anyhow!("Downloaded animation is empty"));
+    }
+    if !file.starts_with(b"<roblox") {
+        return Err(anyhow::anyhow!(
+            "Downloaded file isn't a Roblox model ({} bytes)",
+            file.len()
+        ));
+    }
+    Ok(())
+}
+
+impl AnimationUploader {
+    /// Streams animations through resolve -> download -> validate -> upload: each animation
+    /// is handed to one of `task_count` workers as soon as its metadata batch comes back,
+    /// and every finished upload is sent to `uploaded` right away.
+    ///
+    /// * Notes
+    /// The channels are bounded, so `uploaded` must be drained while the pipeline runs
+    /// (e.g. from a spawned task that saves the mapping as entries arrive). On cancellation
+    /// no new batches or downloads start, uploads in flight finish and are still sent.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let (sender, mut receiver) = tokio::sync::mpsc::channel(PIPELINE_CAPACITY);
+    /// let saver = tokio::spawn(async move {
+    ///     let mut document = MappingDocument::new();
+    ///     while let Some((old_id, entry)) = receiver.recv().await {
+    ///         document.insert(old_id, entry);
+    ///         document.save("mapping.json")?;
+    ///     }
+    ///     anyhow::Ok(document)
+    /// });
+    ///
+    /// let uploader = Arc::new(parser.animation_uploader()?);
+    /// let summary = uploader
+    ///     .reupload_pipeline(asset_ids, AssetType::Animation, None, Some(5), sender)
+    ///     .await?;
+    /// let document = saver.await??;
+    /// ```
+    pub async fn reupload_pipeline(
+        self: Arc<Self>,
+        asset_ids: Vec<u64>,
+        asset_type: AssetType,
+        group_id: Option<u64>,
+        task_count: Option<u64>,
+        uploaded: mpsc::Sender<(String, MappingEntry)>,
+    ) -> anyhow::Result<PipelineSummary> {
+        let (queue_sender, queue_receiver) = mpsc::channel(PIPELINE_CAPACITY);
+        let queue_receiver = Arc::new(Mutex::new(queue_receiver));
+
+        let mut workers = JoinSet::new();
+        for _ in 0..task_count.unwrap_or(5).max(1) {
+            let uploader = Arc::clone(&self);
+            let queue = Arc::clone(&queue_receiver);
+            let uploaded = uploaded.clone();
+            workers.spawn(async move { uploader.pipeline_worker(queue, group_id, uploaded).await });
+        }
+        drop(uploaded);
+
+        let queued = self
+            .stream_metadata(asset_ids, asset_type, queue_sender)
+            .await;
+
+        let mut summary = PipelineSummary::default();
+        while let Some(worker) = workers.join_next().await {
+            match worker {
+                Ok(worker_summary) => summary.add(worker_summary),
+                Err(join_error) => warn!("Pipeline worker failed: {}", join_error),
+            }
+        }
+
+        // Whatever the workers didn't pick up before cancellation
+        let mut queue = queue_receiver.lock().await;
+        while queue.try_recv().is_ok() {
+            summary.abandoned += 1;
+        }
+
+        summary.queued = queued?;
+        Ok(summary)
+    }
+
+    /// Fetches metadata batches, `batch_concurrency` at a time, and queues each animation
+    /// with a download location as its batch arrives. Returns how many were queued.
+    async fn stream_metadata(
+        &self,
+        asset_ids: Vec<u64>,
+        asset_type: AssetType,
+        queue: mpsc::Sender<(String, String)>,
+    ) -> anyhow::Result<usize> {
+        let mut batches = futures::stream::iter(asset_ids.chunks(BATCH_SIZE))
+            .map(|batch| async move {
+                if self.cancellation.is_cancelled() {
+                    return anyhow::Ok(Vec::new());
+                }
+                let responses = self.fetch_batch_with_retry(batch).await?;
+                self.progress.emit(ProgressEvent::BatchFetched {
+                    requested: batch.len(),
+                    fetched: responses.len(),
+                });
+                anyhow::Ok(responses)
+            })
+            .buffer_unordered(self.batch_concurrency.max(1));
+
+        let mut queued = 0;
+        while let Some(responses) = batches.next().await {
+            let animations: Vec<(String, String)> = responses?
+                .into_iter()
+                .filter(|response| {
+                    response.asset_type_id.is_none_or(|type_id| {
+                        asset_type.asset_type_ids().contains(&(type_id as u64))
+                    })
+                })
+                .filter_map(|response| {
+                    let location = response
+                        .locations
+                        .as_ref()
+                        .and_then(|locs| locs.first())
+                        .and_then(|loc| loc.location.clone())?;
+                    Some((response.request_id?, location))
+                })
+                .collect();
+
+            self.progress.emit(ProgressEvent::UploadsQueued {
+                count: animations.len(),
+            });
+            for animation in animations {
+                let sent = tokio::select! {
+                    _ = self.cancellation.cancelled() => return Ok(queued),
+                    sent = queue.send(animation) => sent,
+                };
+                if sent.is_err() {
+                    return Ok(queued);
+                }
+                queued += 1;
+            }
+        }
+
+        Ok(queued)
+    }
+
+    /// Takes queued animations one at a time until the queue closes or the run is cancelled.
+    async fn pipeline_worker(
+        &self,
+        queue: Arc<Mutex<mpsc::Receiver<(String, String)>>>,
+        group_id: Option<u64>,
+        uploaded: mpsc::Sender<(String, MappingEntry)>,
+    ) -> PipelineSummary {
+        let mut summary = PipelineSummary::default();
+
+        loop {
+            let next = {
+                let mut queue = queue.lock().await;
+                tokio::select! {
+                    _ = self.cancellation.cancelled() => None,
+                    next = queue.recv() => next,
+                }
+            };
+            let Some((asset_id, location)) = next else {
+                break;
+            };
+
+            match self.reupload_animation(&asset_id, location, group_id).await {
+                Ok(Some(entry)) => {
+                    summary.uploaded += 1;
+                    if uploaded.send((asset_id, entry)).await.is_err() {
+                        warn!("Nobody is receiving finished uploads, they won't be in the mapping");
+                    }
+                }
+                Ok(None) => summary.abandoned += 1,
+                // Already logged and reported by reupload_animation
+                Err(_) => summary.failed += 1,
+            }
+        }
+
+        summary
+    }
+}
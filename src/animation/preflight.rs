@@ -0,0 +1,163 @@
+use crate::AnimationUploader;
+use crate::rate_limit::{ApiFamily, check_rate_limited};
+use roboat::{ClientBuilder, RoboatError};
+use serde::Deserialize;
+
+/// What the preflight check found out about the cookie.
+#[derive(Debug, Clone)]
+pub struct Preflight {
+    pub user_id: u64,
+    /// Set when a group was checked.
+    pub group: Option<GroupAccess>,
+}
+
+/// The authenticated user's standing in the group animations are uploaded to.
+#[derive(Debug, Clone)]
+pub struct GroupAccess {
+    pub group_id: u64,
+    pub role: String,
+    pub rank: u64,
+}
+
+#[derive(Deserialize)]
+struct GroupMembership {
+    #[serde(rename = "userRole")]
+    user_role: Option<UserRole>,
+    permissions: Option<GroupPermissions>,
+}
+
+#[derive(Deserialize)]
+struct UserRole {
+    role: Option<Role>,
+}
+
+#[derive(Deserialize)]
+struct Role {
+    name: String,
+    rank: u64,
+}
+
+#[derive(Deserialize)]
+struct GroupPermissions {
+    #[serde(rename = "groupEconomyPermissions")]
+    economy: Option<EconomyPermissions>,
+}
+
+#[derive(Deserialize)]
+struct EconomyPermissions {
+    #[serde(rename = "createItems", default)]
+    create_items: bool,
+}
+
+/// Group owners have every permission, whatever the role says.
+const OWNER_RANK: u64 = 255;
+
+impl AnimationUploader {
+    /// Checks the cookie before any real work: it must authenticate, and when uploading to a
+    /// group, the user must be a member whose role can create group items.
+    ///
+    /// * Notes
+    /// Without this, a bad cookie or missing group permission only shows up as BadRequest
+    /// uploads after the whole metadata phase.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let uploader = parser.animation_uploader()?;
+    /// let preflight = uploader.preflight(Some(123456)).await?;
+    /// println!("Uploading as user {}", preflight.user_id);
+    /// ```
+    pub async fn preflight(&self, group_id: Option<u64>) -> anyhow::Result<Preflight> {
+        let user_id = self.authenticated_user_id().await?;
+
+        let group = match group_id {
+            Some(group_id) => Some(self.group_access(group_id).await?),
+            None => None,
+        };
+
+        Ok(Preflight { user_id, group })
+    }
+
+    /// Resolves the user the cookie belongs to.
+    pub async fn authenticated_user_id(&self) -> anyhow::Result<u64> {
+        let client = ClientBuilder::new()
+            .roblosecurity(self.roblosecurity.expose().to_string())
+            .build();
+
+        let client = &client;
+        self.call_api(ApiFamily::Account, move || async move {
+            Ok(client.user_id().await?)
+        })
+        .await
+        .map_err(|e| match e.downcast_ref::<RoboatError>() {
+            Some(RoboatError::InvalidRoblosecurity) => {
+                anyhow::anyhow!("The cookie is invalid or expired, log in again and copy a new one")
+            }
+            _ => anyhow::anyhow!(
+                "Couldn't authenticate with the cookie: {}",
+                self.roblosecurity.redact(&e.to_string())
+            ),
+        })
+    }
+
+    /// Checks that the authenticated user can upload to a group.
+    pub async fn group_access(&self, group_id: u64) -> anyhow::Result<GroupAccess> {
+        let client = reqwest::Client::new();
+        let url = format!(
+            "{}/v1/groups/{}/membership",
+            self.endpoints.groups.trim_end_matches('/'),
+            group_id
+        );
+        let cookie = format!(".ROBLOSECURITY={}", self.roblosecurity.expose());
+
+        let client = &client;
+        let url = &url;
+        let cookie = &cookie;
+        let membership: GroupMembership = self
+            .call_api(ApiFamily::Account, move || async move {
+                let response = client
+                    .get(url)
+                    .header(reqwest::header::COOKIE, cookie)
+                    .send()
+                    .await?;
+                let response = check_rate_limited(response)?.error_for_status()?;
+                Ok(serde_json::from_slice(&response.bytes().await?)?)
+            })
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Couldn't check membership of group {}: {}",
+                    group_id,
+                    self.roblosecurity.redact(&e.to_string())
+                )
+            })?;
+
+        let role = membership
+            .user_role
+            .and_then(|user_role| user_role.role)
+            .filter(|role| role.rank > 0)
+            .ok_or_else(|| {
+                anyhow::anyhow!("The cookie's account is not a member of group {}", group_id)
+            })?;
+
+        let can_create_items = role.rank == OWNER_RANK
+            || membership
+                .permissions
+                .and_then(|permissions| permissions.economy)
+                .is_some_and(|economy| economy.create_items);
+
+        if !can_create_items {
+            return Err(anyhow::anyhow!(
+                "Role '{}' in group {} can't upload animations; give it the \"Create and edit group items\" permission",
+                role.name,
+                group_id
+            ));
+        }
+
+        Ok(GroupAccess {
+            group_id,
+            role: role.name,
+            rank: role.rank,
+        })
+    }
+}
@@ -0,0 +1,113 @@
+use crate::AnimationUploader;
+use crate::Creator;
+use crate::places::PlaceCache;
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// How many asset info lookups run at once; the asset info rate limiter still paces them.
+pub const MAX_CONCURRENT_LOOKUPS: usize = 10;
+
+/// What place lookups learn during a run: each asset's creator, each creator's games and
+/// the place that worked for each creator.
+///
+/// * Notes
+/// Shared (behind an `Arc`) by every uploader created from the same parser, so the
+/// creator filter, every metadata batch and the upload records look each asset up once.
+///
+/// # Examples
+///
+/// ```rust
+/// let resolver = Arc::new(PlaceResolver::new(PlaceCache::load("place-cache.json")?));
+/// let uploader = AnimationUploader::new(cookie).with_place_resolver(resolver.clone());
+/// let place_id = uploader.place_id(507766388).await?;
+/// resolver.place_cache().save("place-cache.json")?;
+/// ```
+#[derive(Debug, Default)]
+pub struct PlaceResolver {
+    /// Asset ID -> creator, from the asset info API.
+    creators: Mutex<HashMap<u64, Creator>>,
+    /// Creator -> root places of its games, listed at most once per run.
+    discovered: Mutex<HashMap<Creator, Vec<u64>>>,
+    /// Creator -> place that worked, saved between runs, plus config overrides.
+    places: Mutex<PlaceCache>,
+}
+
+impl PlaceResolver {
+    pub fn new(places: PlaceCache) -> Self {
+        Self {
+            places: Mutex::new(places),
+            ..Default::default()
+        }
+    }
+
+    /// The creator of an asset, when it was already looked up.
+    pub fn cached_creator(&self, asset_id: u64) -> Option<Creator> {
+        self.creators.lock().unwrap().get(&asset_id).copied()
+    }
+
+    pub fn remember_creator(&self, asset_id: u64, creator: Creator) {
+        self.creators.lock().unwrap().insert(asset_id, creator);
+    }
+
+    /// The games listed for a creator earlier in the run.
+    pub fn cached_games(&self, creator: Creator) -> Option<Vec<u64>> {
+        self.discovered.lock().unwrap().get(&creator).cloned()
+    }
+
+    pub fn remember_games(&self, creator: Creator, places: Vec<u64>) {
+        self.discovered.lock().unwrap().insert(creator, places);
+    }
+
+    /// The override and the cached place of a creator, in the order they should be tried.
+    pub fn known_places(&self, creator: Creator) -> Vec<u64> {
+        self.places.lock().unwrap().known_places(creator)
+    }
+
+    /// Records a place that resolved one of the creator's assets.
+    pub fn remember_place(&self, creator: Creator, place_id: u64) {
+        self.places.lock().unwrap().remember(creator, place_id);
+    }
+
+    /// A copy of the creator -> place cache, e.g. to save it for later runs.
+    pub fn place_cache(&self) -> PlaceCache {
+        self.places.lock().unwrap().clone()
+    }
+}
+
+impl AnimationUploader {
+    /// Same as `asset_creator`, answered from the resolver when the asset was looked up
+    /// before.
+    pub async fn cached_asset_creator(&self, asset_id: u64) -> anyhow::Result<Creator> {
+        if let Some(creator) = self.place_resolver.cached_creator(asset_id) {
+            return Ok(creator);
+        }
+
+        let creator = self.asset_creator(asset_id).await?;
+        self.place_resolver.remember_creator(asset_id, creator);
+        Ok(creator)
+    }
+
+    /// Looks up the creators of many assets, `MAX_CONCURRENT_LOOKUPS` at a time, in the
+    /// order given. Assets not reached before cancellation get an error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// for (asset_id, creator) in uploader.asset_creators(&[507766388, 507766666]).await {
+    ///     println!("{} -> {:?}", asset_id, creator);
+    /// }
+    /// ```
+    pub async fn asset_creators(&self, asset_ids: &[u64]) -> Vec<(u64, anyhow::Result<Creator>)> {
+        futures::stream::iter(asset_ids.iter().copied())
+            .map(|asset_id| async move {
+                if self.cancellation.is_cancelled() {
+                    return (asset_id, Err(anyhow::anyhow!("Cancelled")));
+                }
+                (asset_id, self.cached_asset_creator(asset_id).await)
+            })
+            .buffered(MAX_CONCURRENT_LOOKUPS)
+            .collect()
+            .await
+    }
+}
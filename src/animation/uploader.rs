@@ -1,17 +1,61 @@
+use crate::Roblosecurity;
+use crate::animation::pipeline::validate_animation_file;
+use crate::animation::resolver::PlaceResolver;
+use crate::config::Endpoints;
+use crate::mapping::{MappingDocument, MappingEntry};
+use crate::progress::{Progress, ProgressEvent, ProgressSender};
+use crate::rate_limit::{ApiFamily, RateLimits};
+use crate::retry::RetryPolicy;
 use bytes::Bytes;
+use futures::StreamExt;
+use log::{error, info, warn};
 use roboat::ClientBuilder;
 use roboat::RoboatError;
 use roboat::assetdelivery::AssetBatchResponse;
 use roboat::catalog::CreatorType;
 use roboat::ide::ide_types::NewAnimation;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
 
 pub struct AnimationUploader {
-    pub roblosecurity: String,
+    pub roblosecurity: Roblosecurity,
+    /// Rate limiters shared with every uploader built from the same parser.
+    pub rate_limits: Arc<RateLimits>,
+    /// How metadata fetches, downloads and uploads are retried.
+    pub retry_policy: RetryPolicy,
+    /// Cancelled on Ctrl-C; stops fetching and starting new uploads.
+    pub cancellation: CancellationToken,
+    /// Receives download, upload and batch fetch events.
+    pub progress: Progress,
+    /// Base URLs for the requests this crate sends itself.
+    pub endpoints: Endpoints,
+    /// Name of reuploaded animations; `{id}` and `{name}` are filled in per animation.
+    pub name_template: String,
+    /// Original asset ID -> instance name, used for `{name}`.
+    pub animation_names: HashMap<String, String>,
+    /// Caches creators and places for the batch metadata API, shared with the parser.
+    pub place_resolver: Arc<PlaceResolver>,
+    /// How many metadata batches (and creators retried with their places) run at once.
+    pub batch_concurrency: usize,
 }
 
+/// Name used when no template is set, matching what older versions uploaded as.
+pub const DEFAULT_NAME_TEMPLATE: &str = "reuploaded_animation";
+
+/// Most asset IDs the batch metadata API takes per request.
+pub const BATCH_SIZE: usize = 250;
+
+/// Metadata batches fetched at once when no limit is set.
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+
+/// Longest asset name Roblox accepts.
+const MAX_NAME_LENGTH: usize = 50;
+
 #[derive(Debug)]
 pub struct AnimationWithPlace {
     pub animation: AssetBatchResponse,
@@ -25,8 +69,103 @@ pub struct OwnerInfo {
 
 impl AnimationUploader {
     /// Creates a new AnimationUploader with a roblosecurity cookie.
-    pub fn new(roblosecurity: String) -> Self {
-        Self { roblosecurity }
+    pub fn new<S: Into<Roblosecurity>>(roblosecurity: S) -> Self {
+        Self {
+            roblosecurity: roblosecurity.into(),
+            rate_limits: Arc::new(RateLimits::default()),
+            retry_policy: RetryPolicy::default(),
+            cancellation: CancellationToken::new(),
+            progress: Progress::default(),
+            endpoints: Endpoints::default(),
+            name_template: DEFAULT_NAME_TEMPLATE.to_string(),
+            animation_names: HashMap::new(),
+            place_resolver: Arc::new(PlaceResolver::default()),
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
+        }
+    }
+
+    /// Shares rate limiters with other uploaders, so concurrent work backs off together.
+    pub fn with_rate_limits(mut self, rate_limits: Arc<RateLimits>) -> Self {
+        self.rate_limits = rate_limits;
+        self
+    }
+
+    /// Replaces the default retry policy.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Overrides the API base URLs.
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Names reuploaded animations from a template, e.g. `"{name} ({id})"`. `{id}` is the
+    /// original asset ID and `{name}` the name of the instance that referenced it (or the ID
+    /// when only scripts reference it).
+    pub fn with_name_template<S: Into<String>>(mut self, template: S) -> Self {
+        self.name_template = template.into();
+        self
+    }
+
+    /// Sets the names `{name}` is filled in from, keyed by original asset ID.
+    pub fn with_animation_names(mut self, names: HashMap<String, String>) -> Self {
+        self.animation_names = names;
+        self
+    }
+
+    /// Renders the name template for one animation, cut to Roblox's name length limit.
+    pub fn animation_name(&self, asset_id: &str) -> String {
+        let name = self
+            .animation_names
+            .get(asset_id)
+            .map(String::as_str)
+            .unwrap_or(asset_id);
+        self.name_template
+            .replace("{id}", asset_id)
+            .replace("{name}", name)
+            .chars()
+            .take(MAX_NAME_LENGTH)
+            .collect()
+    }
+
+    /// Shares a place resolver, so creators and places found by this uploader are reused by
+    /// others and can be saved for later runs.
+    pub fn with_place_resolver(mut self, place_resolver: Arc<PlaceResolver>) -> Self {
+        self.place_resolver = place_resolver;
+        self
+    }
+
+    /// Sets how many metadata batches are fetched at once. Requests still go through the
+    /// shared rate limiters, so this bounds the work in flight rather than the request rate.
+    pub fn with_batch_concurrency(mut self, batch_concurrency: usize) -> Self {
+        self.batch_concurrency = batch_concurrency.max(1);
+        self
+    }
+
+    /// Shares a cancellation token, so cancelling it stops this uploader's work.
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Sends progress events to a channel, e.g. to drive a progress bar.
+    pub fn with_progress(mut self, sender: ProgressSender) -> Self {
+        self.progress = Progress::new(sender);
+        self
+    }
+
+    /// Sends a request through the family's rate limiter using this uploader's retry policy.
+    pub async fn call_api<T, F, Fut>(&self, family: ApiFamily, request: F) -> anyhow::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        self.rate_limits
+            .call(family, &self.retry_policy, request)
+            .await
     }
 
     /// Uploads animation data to Roblox.
@@ -50,14 +189,25 @@ impl AnimationUploader {
         &self,
         animation_data: Bytes,
         group_id: Option<u64>,
+    ) -> Result<String, RoboatError> {
+        self.upload_animation_named(animation_data, group_id, DEFAULT_NAME_TEMPLATE.to_string())
+            .await
+    }
+
+    /// Same as `upload_animation` with a chosen asset name.
+    pub async fn upload_animation_named(
+        &self,
+        animation_data: Bytes,
+        group_id: Option<u64>,
+        name: String,
     ) -> Result<String, RoboatError> {
         let client = ClientBuilder::new()
-            .roblosecurity(self.roblosecurity.clone())
+            .roblosecurity(self.roblosecurity.expose().to_string())
             .build();
 
         let animation = NewAnimation {
             group_id: group_id,
-            name: "reuploaded_animation".to_string(),
+            name,
             description: "This is a example".to_string(),
             animation_data,
         };
@@ -70,7 +220,12 @@ impl AnimationUploader {
     ///
     /// * Notes
     /// Uses Semaphore for multiproccessing, default it set at 5 semphores
+    /// Downloads and uploads go through the shared rate limiters, which retry
+    /// rate limits and network errors with backoff.
     ///
+    /// When the uploader's cancellation token is cancelled, queued and downloading animations
+    /// are abandoned but uploads already sent are allowed to finish, and the mapping of the
+    /// animations that made it is returned.
     ///
     /// # Example
     /// ```rust
@@ -84,138 +239,267 @@ impl AnimationUploader {
         group_id: Option<u64>,
         task_count: Option<u64>,
     ) -> Result<HashMap<String, String>, RoboatError> {
-        let max_concurrent_tasks = task_count.unwrap_or(500);
+        let document = self
+            .reupload_all_animations_recorded(animations, group_id, task_count)
+            .await?;
+        Ok(document.animation_mapping())
+    }
+
+    /// Same as `reupload_all_animations`, recording the original creator, the uploaded name,
+    /// the group, the upload time and the SHA-256 of each animation in a mapping document.
+    ///
+    /// * Notes
+    /// See `reupload_animation`. Source files are left for the caller, which knows the places.
+    pub async fn reupload_all_animations_recorded(
+        self: Arc<Self>,
+        animations: Vec<AssetBatchResponse>,
+        group_id: Option<u64>,
+        task_count: Option<u64>,
+    ) -> Result<MappingDocument, RoboatError> {
+        let max_concurrent_tasks = task_count.unwrap_or(5);
 
         let semaphore = Arc::new(Semaphore::new(max_concurrent_tasks as usize));
         let mut tasks = Vec::new();
-        let total_animations = animations.len();
-
-        for (index, animation) in animations.into_iter().enumerate() {
-            let location_string = animation
-                .locations
-                .as_ref()
-                .and_then(|locs| locs.first())
-                .and_then(|loc| loc.location.as_ref());
-
-            if let Some(location) = location_string {
-                let semaphore = semaphore.clone();
-                let self_arc = Arc::clone(&self);
-                let location = location.to_string();
-                let request_id = animation.request_id.clone();
-                let group_id = group_id.clone();
-
-                let task = tokio::spawn(async move {
-                    let _permit = semaphore.acquire().await.unwrap();
-                    let animation_file = self_arc.file_bytes_from_url(location).await?;
-
-                    // Retry logic for upload_animation
-                    let max_upload_retries: usize = 5;
-                    let mut last_error = None;
-
-                    for attempt in 1..=max_upload_retries {
-                        match self_arc
-                            .upload_animation(animation_file.clone(), group_id)
-                            .await
-                        {
-                            Ok(new_animation_id) => {
-                                println!(
-                                    "Success uploading animation {}/{} ({} remaining)",
-                                    index + 1,
-                                    total_animations,
-                                    total_animations - (index + 1),
-                                );
-                                return Ok::<_, RoboatError>((request_id, new_animation_id));
-                            }
 
-                            Err(e) => {
-                                eprintln!(
-                                    "Upload attempt {}/{} failed for animation {}: {}",
-                                    attempt,
-                                    max_upload_retries,
-                                    index + 1,
-                                    e
-                                );
-                                last_error = Some(e);
-
-                                // Don't sleep on the last attempt
-                                if attempt < max_upload_retries {
-                                    tokio::time::sleep(tokio::time::Duration::from_millis(1000))
-                                        .await;
-                                }
-                            }
-                        }
-                    }
-
-                    // All retries failed
-                    Err(last_error.unwrap())
-                });
-
-                tasks.push(task);
-            }
+        let queued: Vec<(Option<String>, String)> = animations
+            .into_iter()
+            .filter_map(|animation| {
+                let location = animation
+                    .locations
+                    .as_ref()
+                    .and_then(|locs| locs.first())
+                    .and_then(|loc| loc.location.clone())?;
+                Some((animation.request_id, location))
+            })
+            .collect();
+
+        self.progress.emit(ProgressEvent::UploadsQueued {
+            count: queued.len(),
+        });
+
+        for (request_id, location) in queued {
+            let semaphore = semaphore.clone();
+            let self_arc = Arc::clone(&self);
+            let group_id = group_id.clone();
+
+            let task = tokio::spawn(async move {
+                let asset_id = request_id.clone().unwrap_or_default();
+                let _permit = tokio::select! {
+                    _ = self_arc.cancellation.cancelled() => return Ok(None),
+                    permit = semaphore.acquire() => permit.unwrap(),
+                };
+
+                let entry = self_arc
+                    .reupload_animation(&asset_id, location, group_id)
+                    .await?;
+                anyhow::Ok(entry.map(|entry| (request_id, entry)))
+            });
+
+            tasks.push(task);
         }
 
-        let mut animation_hashmap = HashMap::new();
+        let mut document = MappingDocument::new();
         let mut errors = Vec::new();
+        let mut abandoned = 0;
         let total_tasks = tasks.len();
 
         for task in tasks {
             match task.await {
                 // Task completed successfully with a result and request_id exists
-                Ok(Ok((Some(request_id), new_animation_id))) => {
-                    animation_hashmap.insert(request_id, new_animation_id);
+                Ok(Ok(Some((Some(request_id), entry)))) => {
+                    document.insert(request_id, entry);
                 }
 
                 // Handle case where animation_id is None
-                Ok(Ok((None, _))) => {
-                    eprintln!("Warning: Animation uploader success but no animation_id available");
+                Ok(Ok(Some((None, _)))) => {
+                    warn!("Animation uploader success but no animation_id available");
+                }
+
+                // Cancelled before the upload started
+                Ok(Ok(None)) => {
+                    abandoned += 1;
                 }
 
                 // Task completed but your function returned an error
                 Ok(Err(e)) => {
-                    if matches!(e, RoboatError::BadRequest) {
-                        eprintln!(
-                            "Animation Upload API failed to respond with errors; Cookie cannot publish animations. \nwith group uploading; make sure the cookie has perms to ALL Asset and Experience permissions\n"
+                    if matches!(
+                        e.downcast_ref::<RoboatError>(),
+                        Some(RoboatError::BadRequest)
+                    ) {
+                        error!(
+                            "Animation Upload API failed to respond with errors; Cookie cannot publish animations. With group uploading, make sure the cookie has perms to ALL Asset and Experience permissions"
                         )
                     } else {
-                        eprintln!("Animation upload failed: {}", e);
+                        error!(
+                            "Animation upload failed: {}",
+                            self.roblosecurity.redact(&e.to_string())
+                        );
                     }
                     errors.push(e);
                 }
 
                 // Task panicked or was cancelled
                 Err(join_error) => {
-                    eprintln!("Task failed to execute: {}", join_error);
+                    error!("Task failed to execute: {}", join_error);
                     // Just log the error and continue - don't fail the entire batch
                 }
             }
         }
 
+        if abandoned > 0 {
+            warn!(
+                abandoned = abandoned, total = total_tasks;
+                "Cancelled: {} of {} animations were not uploaded",
+                abandoned, total_tasks
+            );
+        }
+
         // Handle collected errors
         if !errors.is_empty() {
-            eprintln!(
-                "Some uploads failed: {} out of {} tasks\nERROR INFO: {:?}",
+            error!(
+                failed = errors.len(), total = total_tasks;
+                "Some uploads failed: {} out of {} tasks",
                 errors.len(),
-                total_tasks,
-                errors
+                total_tasks
             );
         }
 
-        Ok(animation_hashmap)
+        Ok(document)
+    }
+
+    /// Downloads, validates and reuploads one animation, recording the upload in a mapping
+    /// entry. Returns None when cancelled before the upload started.
+    ///
+    /// * Notes
+    /// Used by `reupload_all_animations` and the streaming pipeline, which limit how many
+    /// run at once. The original creator costs one asset info request unless the place
+    /// resolver already knows it; when it fails the entry is kept without one.
+    pub async fn reupload_animation(
+        &self,
+        asset_id: &str,
+        location: String,
+        group_id: Option<u64>,
+    ) -> anyhow::Result<Option<MappingEntry>> {
+        let progress = &self.progress;
+        let asset_id = asset_id.to_string();
+
+        progress.emit(ProgressEvent::DownloadStarted {
+            asset_id: asset_id.clone(),
+        });
+        let animation_file = tokio::select! {
+            _ = self.cancellation.cancelled() => return Ok(None),
+            file = self.file_bytes_from_url(location) => file,
+        };
+        let animation_file =
+            match animation_file.and_then(|file| validate_animation_file(&file).map(|_| file)) {
+                Ok(animation_file) => animation_file,
+                Err(e) => {
+                    let message = self.roblosecurity.redact(&e.to_string());
+                    warn!(asset_id = asset_id.as_str(); "Download failed: {}", message);
+                    progress.emit(ProgressEvent::UploadFailed {
+                        asset_id,
+                        error: message,
+                    });
+                    return Err(e);
+                }
+            };
+        progress.emit(ProgressEvent::DownloadFinished {
+            asset_id: asset_id.clone(),
+        });
+        let sha256 = format!("{:x}", Sha256::digest(&animation_file));
+
+        // Not cancelled or timed out from here on: an upload Roblox already accepted would
+        // be lost from the mapping, and retrying it would create a duplicate asset. The rate
+        // limiter only retries uploads that were turned away (429) or never connected.
+        let uploader = self;
+        let animation_file = &animation_file;
+        let name = &self.animation_name(&asset_id);
+        let uploaded = self
+            .call_api(ApiFamily::Upload, move || async move {
+                Ok(uploader
+                    .upload_animation_named(animation_file.clone(), group_id, name.clone())
+                    .await?)
+            })
+            .await;
+
+        let new_animation_id = match uploaded {
+            Ok(new_animation_id) => new_animation_id,
+            Err(e) => {
+                let message = self.roblosecurity.redact(&e.to_string());
+                warn!(asset_id = asset_id.as_str(); "Upload failed: {}", message);
+                progress.emit(ProgressEvent::UploadFailed {
+                    asset_id,
+                    error: message,
+                });
+                return Err(e);
+            }
+        };
+
+        info!(
+            asset_id = asset_id.as_str(), new_asset_id = new_animation_id.as_str();
+            "Reuploaded animation"
+        );
+        progress.emit(ProgressEvent::UploadSucceeded {
+            asset_id: asset_id.clone(),
+            new_asset_id: new_animation_id.clone(),
+        });
+
+        let creator = match asset_id.parse::<u64>() {
+            Ok(original_id) => self.cached_asset_creator(original_id).await,
+            Err(e) => Err(e.into()),
+        };
+        let creator = match creator {
+            Ok(creator) => Some(creator.to_string()),
+            Err(e) => {
+                warn!(asset_id = asset_id.as_str(); "Couldn't record the original creator: {}", e);
+                None
+            }
+        };
+
+        Ok(Some(MappingEntry {
+            new_id: new_animation_id,
+            creator,
+            name: Some(name.clone()),
+            group_id,
+            uploaded_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|elapsed| elapsed.as_secs()),
+            sha256: Some(sha256),
+            source_files: Vec::new(),
+        }))
     }
 
     ///  Gets all the animation file data to re-upload them
     /// * Notes
-    /// This func uses caching and hashmaps to handle needing place-id to download assets
+    /// This func uses caching and hashmaps to handle needing place-id to download assets.
+    /// Up to `batch_concurrency` batches run at once, sharing the place resolver and the
+    /// rate limiters; results keep the order of `asset_ids`. Batches not started before
+    /// cancellation are skipped, returning the batches fetched so far.
     pub async fn fetch_animation_assets(
         &self,
         asset_ids: Vec<u64>,
     ) -> anyhow::Result<Vec<AssetBatchResponse>> {
-        let mut animations: Vec<AssetBatchResponse> = Vec::new();
-        let batch_size = 250;
+        let batches: Vec<anyhow::Result<Option<Vec<AssetBatchResponse>>>> =
+            futures::stream::iter(asset_ids.chunks(BATCH_SIZE))
+                .map(|batch| async move {
+                    if self.cancellation.is_cancelled() {
+                        return anyhow::Ok(None);
+                    }
+                    let batch_animations = self.fetch_batch_with_retry(batch).await?;
+                    self.progress.emit(ProgressEvent::BatchFetched {
+                        requested: batch.len(),
+                        fetched: batch_animations.len(),
+                    });
+                    anyhow::Ok(Some(batch_animations))
+                })
+                .buffered(self.batch_concurrency.max(1))
+                .collect()
+                .await;
 
-        for batch in asset_ids.chunks(batch_size) {
-            let batch_animations = self.fetch_batch_with_retry(batch).await?;
-            animations.extend(batch_animations);
+        let mut animations: Vec<AssetBatchResponse> = Vec::new();
+        for batch_animations in batches {
+            animations.extend(batch_animations?.unwrap_or_default());
         }
 
         Ok(animations)
@@ -223,19 +507,13 @@ impl AnimationUploader {
 }
 
 mod internal {
-    use anyhow::Context;
-    use std::{
-        collections::{HashMap, HashSet},
-        time::Duration,
-    };
-    use tokio::{io::DuplexStream, time};
+    use std::collections::{HashMap, HashSet};
 
-    use roboat::{
-        RoboatError,
-        assetdelivery::{AssetBatchPayload, AssetBatchResponse},
-    };
+    use log::{debug, error, warn};
+    use roboat::assetdelivery::{AssetBatchPayload, AssetBatchResponse};
 
-    use crate::AnimationUploader;
+    use crate::{AnimationUploader, Creator};
+    use futures::StreamExt;
 
     impl AnimationUploader {
         /// Fetches asset metadata for a batch of asset IDs with automatic retry logic and 403 error handling.
@@ -252,32 +530,26 @@ mod internal {
         /// * `Err(anyhow::Error)` - Failed after all retry attempts or encountered unrecoverable error
         ///
         /// # Retry Logic
-        /// - **Max Retries**: 9 attempts with exponential timeout increase
-        /// - **403 Errors**: Automatically discovers place_id from first 403 error and retries
-        /// - **Network Errors**: Retries with increased timeout for reqwest timeout/malformed response errors
-        /// - **Timeout Progression**: Starts at 4 seconds, increases by 1 second per retry attempt
+        /// - **Rate limits and network errors**: Retried by the batch metadata rate limiter,
+        ///   with exponential backoff and a bounded retry budget
+        /// - **403 Errors**: Retried per creator with its override, its cached place, then
+        ///   each of its games until one works
         ///
         /// # Error Handling
-        /// - **403 Forbidden**: Extracts place_id from the failing asset and retries the entire batch
-        /// - **Timeout/Network**: Implements backoff strategy with 2-second sleep between attempts
+        /// - **403 Forbidden**: Groups the failing assets by creator and retries them with the
+        ///   creator's places, remembering the place that worked
         /// - **Other Errors**: Fails immediately without retry
         ///
         /// # Examples
         /// ```rust
-        /// let mut cache = HashMap::new();
-        /// let mut place_id = None;
         /// let asset_ids = vec![123456, 789012, 345678];
         ///
-        /// let animations = uploader
-        ///     .fetch_batch_with_retry(&asset_ids, &mut cache, &mut place_id)
-        ///     .await?;
+        /// let animations = uploader.fetch_batch_with_retry(&asset_ids).await?;
         /// ```
-        pub(super) async fn fetch_batch_with_retry(
+        pub(crate) async fn fetch_batch_with_retry(
             &self,
             asset_ids: &[u64],
         ) -> anyhow::Result<Vec<AssetBatchResponse>> {
-            use tokio::time::{Duration, sleep};
-
             // NOTE:
             // 1. Try the asset_ids once.
             // 2. Make a hashmap for failed ids
@@ -285,106 +557,82 @@ mod internal {
             // 4. After scanning all the responses resolve the errors
             // (Retry the places with the place found once and if it doesnt work dont resolve it)
 
-            // Have placeid: vec[asset_ids] hashmap
-            let mut failed_ids: HashMap<u64, Vec<u64>> = HashMap::new();
+            // Asset IDs that failed with the initial place
+            let mut failed_assets: Vec<u64> = Vec::new();
 
             // get place id
             let init_place_id = self.get_initial_place(asset_ids).await.unwrap_or(0);
             //
             let mut sucess_responses: Vec<AssetBatchResponse> = Vec::new();
-            const MAX_RETRIES: u32 = 9;
-            let mut attempts = 0;
-            let mut timeout_seconds: u64 = 4;
-
-            loop {
-                let initial_payload = self.create_batch_payloads(asset_ids);
-
-                match self
-                    .check_asset_metadata(initial_payload, init_place_id, Duration::from_secs(5))
-                    .await
-                {
-                    Ok(Some(responses)) => {
-                        // look for success and fails
-                        for response in responses {
-                            if response.errors.is_none() {
-                                // make asset_id a u64
-                                println!("sucessfully fetched animation details");
-                                sucess_responses.push(response);
-                            } else if response.errors.is_some() {
-                                // println!("errors is {:?}", response.errors);
-                                // if the response has error then map it in failed_ids
-                                let request_id = response.request_id;
-                                // make asset_id a u64
-                                if let Some(asset_id) =
-                                    request_id.and_then(|s| s.parse::<u64>().ok())
-                                {
-                                    match self
-                                        .get_or_fetch_place_id(asset_id, &mut failed_ids)
-                                        .await
-                                    {
-                                        Ok(place_id) => {
-                                            // Make a key of the place_id or if its there make the list
-                                            // bigger with the asset_id
-                                            println!(
-                                                "Found place_id: {} for asset: {}",
-                                                place_id, asset_id
-                                            );
-
-                                            failed_ids
-                                                .entry(place_id)
-                                                .or_insert_with(Vec::new)
-                                                .push(asset_id);
-                                        }
-                                        Err(e) => {
-                                            println!("failed to get place id: {}", e);
-                                        }
-                                    }
-                                }
-                            } else {
-                                println!("request_id is None request: {:?} BREAKING", response);
+
+            let initial_payload = self.create_batch_payloads(asset_ids);
+
+            match self
+                .check_asset_metadata(
+                    initial_payload,
+                    init_place_id,
+                    self.retry_policy.longest_timeout(),
+                )
+                .await
+            {
+                Ok(Some(responses)) => {
+                    // look for success and fails
+                    for response in responses {
+                        if response.errors.is_none() {
+                            // make asset_id a u64
+                            debug!(asset_id = response.request_id.as_deref().unwrap_or_default(); "Fetched asset details");
+                            sucess_responses.push(response);
+                        } else {
+                            // if the response has error then retry it with its creator's places
+                            let request_id = response.request_id;
+                            // make asset_id a u64
+                            if let Some(asset_id) = request_id.and_then(|s| s.parse::<u64>().ok()) {
+                                failed_assets.push(asset_id);
                             }
                         }
-                        break;
-                    }
-                    Ok(None) => {
-                        println!("got no responses");
-                        break;
                     }
+                }
+                Ok(None) => {
+                    warn!(place_id = init_place_id; "Batch metadata API returned no responses");
+                }
+                Err(e) => {
+                    error!(place_id = init_place_id; "Error checking asset metadata: {}", e);
+                }
+            }
+
+            // Creators are cached by the resolver, so assets looked up for the initial place or
+            // an earlier batch cost nothing here
+            let mut failed_ids: HashMap<Creator, Vec<u64>> = HashMap::new();
+            for (asset_id, creator) in self.asset_creators(&failed_assets).await {
+                match creator {
+                    Ok(creator) => failed_ids.entry(creator).or_default().push(asset_id),
                     Err(e) => {
-                        println!("error checking asset metadata: {}", e);
-                        if self.should_retry(&e, attempts, MAX_RETRIES) {
-                            attempts += 1;
-                            timeout_seconds += 1;
-                            println!(
-                                "Request failed, retrying with higher timeout: attempts {}/{} ({})",
-                                attempts, MAX_RETRIES, e
-                            );
-
-                            sleep(Duration::from_secs(2)).await;
-                            continue;
-                        }
-                        break;
-                        // break; // or handle error as appropriate
+                        warn!(asset_id = asset_id; "Failed to get creator: {}", e);
                     }
                 }
             }
 
-            // TODO: resolve places
-            let mut resolved_responses = self.resolve_assets_with_places(failed_ids).await;
-            sucess_responses.append(&mut resolved_responses);
+            // Each creator's places are tried in turn, but creators are retried concurrently
+            let resolved: Vec<Vec<AssetBatchResponse>> = futures::stream::iter(failed_ids)
+                .map(|(creator, asset_ids)| self.resolve_creator_assets(creator, asset_ids))
+                .buffer_unordered(self.batch_concurrency.max(1))
+                .collect()
+                .await;
+            for mut resolved_responses in resolved {
+                sucess_responses.append(&mut resolved_responses);
+            }
             return Ok(sucess_responses);
         }
 
         /// Returns Place Id (String) and asset_id (u64)
         pub(super) async fn get_initial_place(&self, asset_ids: &[u64]) -> anyhow::Result<u64> {
-            let mut empty_map: HashMap<u64, Vec<u64>> = HashMap::new();
             for asset_id in asset_ids {
-                match self.get_or_fetch_place_id(*asset_id, &mut empty_map).await {
+                match self.place_id(*asset_id).await {
                     Ok(place_id) => {
                         return Ok(place_id);
                     }
                     Err(e) => {
-                        eprintln!("Error getting place for asset {}: {:?}", asset_id, e);
+                        warn!(asset_id = *asset_id; "Error getting place for asset: {}", e);
                         continue;
                     }
                 }
@@ -394,111 +642,101 @@ mod internal {
             ))
         }
 
-        /// Helper func for fetch_batch_with_retry to resolve all the 403 errors that have to do
-        /// with place_id not being in headers
-        pub(super) async fn resolve_assets_with_places(
+        /// Helper func for fetch_batch_with_retry to resolve the 403 errors of one creator's
+        /// assets: tries the creator's override and cached place, then its games, until every
+        /// asset resolved or the places ran out. Places that resolve anything are cached.
+        pub(super) async fn resolve_creator_assets(
             &self,
-            asset_and_places: HashMap<u64, Vec<u64>>,
+            creator: Creator,
+            asset_ids: Vec<u64>,
         ) -> Vec<AssetBatchResponse> {
-            use tokio::time::Duration;
+            let mut remaining = asset_ids;
             let mut resolved_responses: Vec<AssetBatchResponse> = Vec::new();
+            let mut tried: HashSet<u64> = HashSet::new();
 
-            for (place_id, vec_assets) in asset_and_places {
-                let payload = self.create_batch_payloads(&vec_assets);
-
-                match self
-                    .check_asset_metadata(payload, place_id, Duration::from_secs(5))
-                    .await
-                {
-                    Ok(Some(responses)) => {
-                        // debug
-                        for response in responses {
-                            if response.errors.is_none() {
-                                println!("sucessfully resolved error: {:?}", response.request_id);
-                                resolved_responses.push(response);
-                            } else {
-                                eprintln!(
-                                    "error not resolved for {:?} after using place_id {:?} in headers \n response is {:?}",
-                                    response.request_id, place_id, response
-                                );
-                            }
-                        }
+            let known_places = self.place_resolver.known_places(creator);
+            for place_id in known_places {
+                if remaining.is_empty() || self.cancellation.is_cancelled() {
+                    return resolved_responses;
+                }
+                tried.insert(place_id);
+                self.resolve_with_place(creator, place_id, &mut remaining, &mut resolved_responses)
+                    .await;
+            }
+
+            if !remaining.is_empty() {
+                let discovered = match self.discovered_places(creator).await {
+                    Ok(places) => places,
+                    Err(e) => {
+                        warn!(creator = creator.to_string().as_str(); "Couldn't list games: {}", e);
+                        Vec::new()
                     }
-                    Ok(None) => {
-                        println!("got no response");
+                };
+                for place_id in discovered {
+                    if remaining.is_empty() || self.cancellation.is_cancelled() {
+                        break;
                     }
-                    Err(e) => {
-                        eprintln!("{:?}", e);
+                    if !tried.insert(place_id) {
+                        continue;
                     }
+                    self.resolve_with_place(
+                        creator,
+                        place_id,
+                        &mut remaining,
+                        &mut resolved_responses,
+                    )
+                    .await;
                 }
             }
-            return resolved_responses;
-        }
 
-        /// Fetches the assets place id by calling the internal place_id func. will skip this
-        /// function if place_id was already found
-        pub(super) async fn get_or_fetch_place_id(
-            &self,
-            asset_id: u64,
-            cached_places: &mut HashMap<u64, Vec<u64>>, // place_id -> asset_ids
-        ) -> anyhow::Result<u64> {
-            // Try to find if asset_id is already recorded
-            for (place_id, assets) in cached_places.iter() {
-                if assets.contains(&asset_id) {
-                    println!(
-                        "Found place_id {} in cache for asset {}",
-                        place_id, asset_id
-                    );
-                    return Ok(*place_id);
-                }
+            for asset_id in remaining {
+                warn!(
+                    asset_id = asset_id, creator = creator.to_string().as_str();
+                    "Asset failed with every place of its creator, set one under [places] in the config"
+                );
             }
+            resolved_responses
+        }
 
-            loop {
-                match self.place_id(asset_id, cached_places).await {
-                    Ok(place_id) => {
-                        cached_places.entry(place_id).or_default().push(asset_id);
-                        return Ok(place_id);
-                    }
-                    Err(e) => {
-                        if let Some(RoboatError::TooManyRequests) = e.downcast_ref::<RoboatError>()
-                        {
-                            println!(
-                                "place_id fetching got ratelimited waiting 4 seconds then retrying.."
-                            );
-                            time::sleep(Duration::from_secs(4)).await;
+        /// Retries `remaining` with one place header, moving what resolves into `resolved`.
+        pub(super) async fn resolve_with_place(
+            &self,
+            creator: Creator,
+            place_id: u64,
+            remaining: &mut Vec<u64>,
+            resolved: &mut Vec<AssetBatchResponse>,
+        ) {
+            let payload = self.create_batch_payloads(remaining);
+
+            match self
+                .check_asset_metadata(payload, place_id, self.retry_policy.longest_timeout())
+                .await
+            {
+                Ok(Some(responses)) => {
+                    let before = resolved.len();
+                    for response in responses {
+                        let asset_id = response.request_id.clone().unwrap_or_default();
+                        if response.errors.is_none() {
+                            debug!(asset_id = asset_id.as_str(), place_id = place_id; "Resolved asset with place header");
+                            remaining.retain(|id| id.to_string() != asset_id);
+                            resolved.push(response);
                         } else {
-                            return Err(anyhow::anyhow!(
-                                "got error other than too many requests: {}",
-                                e
-                            ));
+                            debug!(asset_id = asset_id.as_str(), place_id = place_id; "Unresolved response: {:?}", response);
                         }
                     }
+                    if resolved.len() > before {
+                        self.place_resolver.remember_place(creator, place_id);
+                    }
                 }
-            }
-            // .with_context(|| format!("Failed to get place id for asset {}", asset_id))?;
-        }
-
-        pub(super) fn should_retry(
-            &self,
-            error: &anyhow::Error,
-            attempts: u32,
-            max_retries: u32,
-        ) -> bool {
-            use roboat::RoboatError;
-            if attempts >= max_retries {
-                return false;
-            }
-
-            if let Some(roboat_error) = error.downcast_ref::<RoboatError>() {
-                match roboat_error {
-                    RoboatError::ReqwestError(reqwest_err) if reqwest_err.is_timeout() => true,
-                    RoboatError::MalformedResponse => true,
-                    _ => false,
+                Ok(None) => {
+                    warn!(place_id = place_id; "Batch metadata API returned no responses");
+                }
+                Err(e) => {
+                    error!(place_id = place_id; "Error checking asset metadata: {}", e);
                 }
-            } else {
-                false
             }
         }
+
         ///
         /// Takes in a vector of asset_ids then formats them in the payload that roblox expects
         pub(super) fn create_batch_payloads(&self, asset_ids: &[u64]) -> Vec<AssetBatchPayload> {
@@ -0,0 +1,104 @@
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// The kinds of assets the scanner and replacer know how to find in a place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub enum AssetType {
+    Animation,
+    Audio,
+    Mesh,
+    Image,
+}
+
+impl AssetType {
+    pub const ALL: [AssetType; 4] = [
+        AssetType::Animation,
+        AssetType::Audio,
+        AssetType::Mesh,
+        AssetType::Image,
+    ];
+
+    /// The `(class, property)` pairs that reference this asset type.
+    pub fn properties(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            AssetType::Animation => &[("Animation", "AnimationId")],
+            AssetType::Audio => &[("Sound", "SoundId")],
+            AssetType::Mesh => &[
+                ("MeshPart", "MeshId"),
+                ("SpecialMesh", "MeshId"),
+                ("FileMesh", "MeshId"),
+            ],
+            AssetType::Image => &[
+                ("Decal", "Texture"),
+                ("Texture", "Texture"),
+                ("ImageLabel", "Image"),
+                ("ImageButton", "Image"),
+                ("MeshPart", "TextureID"),
+                ("SpecialMesh", "TextureId"),
+            ],
+        }
+    }
+
+    /// Roblox asset type IDs the batch metadata API reports for this asset type.
+    pub fn asset_type_ids(&self) -> &'static [u64] {
+        match self {
+            AssetType::Animation => &[24],
+            AssetType::Audio => &[3],
+            AssetType::Mesh => &[4, 40],
+            AssetType::Image => &[1, 13],
+        }
+    }
+
+    pub fn from_asset_type_id(asset_type_id: u64) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|asset_type| asset_type.asset_type_ids().contains(&asset_type_id))
+    }
+
+    /// Only animations have an upload API wired up, other types are scanned and reported.
+    pub fn can_reupload(&self) -> bool {
+        matches!(self, AssetType::Animation)
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            AssetType::Animation => "animation",
+            AssetType::Audio => "audio",
+            AssetType::Mesh => "mesh",
+            AssetType::Image => "image",
+        }
+    }
+}
+
+impl TryFrom<String> for AssetType {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl FromStr for AssetType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "animation" | "animations" => Ok(AssetType::Animation),
+            "audio" | "sound" | "sounds" => Ok(AssetType::Audio),
+            "mesh" | "meshes" => Ok(AssetType::Mesh),
+            "image" | "images" | "decal" | "decals" => Ok(AssetType::Image),
+            other => Err(format!(
+                "Unknown asset type '{}' (expected animation, audio, mesh or image)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for AssetType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
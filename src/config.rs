@@ -0,0 +1,188 @@
+use crate::asset::AssetType;
+use crate::retry::{ErrorClass, RetryPolicy};
+use crate::{AnimationFilter, Creator};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// File name looked up in the current directory when no `--config` is given.
+pub const CONFIG_FILE_NAME: &str = "animation-replacer.toml";
+
+/// Project settings from `animation-replacer.toml`, meant to be committed next to a game so
+/// a team shares one setup. Every field is optional and CLI flags override them.
+///
+/// * Notes
+/// Relative file paths (`output`, `mapping_file`, `place_cache_file`) are relative to the
+/// config file, not to the directory the tool runs in.
+///
+/// # Examples
+///
+/// ```toml
+/// group = 123456
+/// threads = 8
+/// batch_concurrency = 4
+/// output = "build/Game.rbxl"
+/// mapping_file = "animation-mapping.json"
+/// place_cache_file = "place-cache.json"
+/// name_template = "{name} ({id})"
+///
+/// [retry]
+/// max_attempts = 10
+/// retry_on = ["rate-limited", "timeout"]
+///
+/// [filters]
+/// skip_creators = ["user:1"]
+/// include_paths = ["ReplicatedStorage/**"]
+///
+/// [places]
+/// "group:123456" = 987654321
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub group: Option<u64>,
+    pub threads: Option<u64>,
+    pub batch_concurrency: Option<usize>,
+    pub output: Option<String>,
+    pub mapping_file: Option<String>,
+    pub place_cache_file: Option<String>,
+    /// Name given to reuploaded animations, see `AnimationUploader::with_name_template`.
+    pub name_template: Option<String>,
+    pub skip_default_animations: Option<bool>,
+    pub asset_type: Option<AssetType>,
+    pub retry: RetryConfig,
+    pub filters: FilterConfig,
+    pub endpoints: Endpoints,
+    /// Place ID to send for a creator's assets when its games can't be discovered,
+    /// e.g. `"group:123" = 456`.
+    pub places: HashMap<Creator, u64>,
+}
+
+/// The `[retry]` table; unset fields keep the policy's defaults.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    pub max_attempts: Option<u32>,
+    pub base_delay_ms: Option<u64>,
+    pub max_delay_ms: Option<u64>,
+    pub timeout_secs: Option<u64>,
+    pub timeout_growth_secs: Option<u64>,
+    pub retry_on: Option<Vec<ErrorClass>>,
+}
+
+/// The `[filters]` table, same meaning as the matching CLI flags.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterConfig {
+    pub only_ids: Vec<u64>,
+    pub skip_ids: Vec<u64>,
+    pub only_creators: Vec<Creator>,
+    pub skip_creators: Vec<Creator>,
+    pub include_paths: Vec<String>,
+    pub exclude_paths: Vec<String>,
+    pub only_scripts: Vec<String>,
+    pub skip_scripts: Vec<String>,
+}
+
+/// Base URLs of the Roblox APIs this crate calls itself. Requests made through roboat use
+/// roboat's own endpoints.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Endpoints {
+    /// Used by the preflight group permission check.
+    pub groups: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            groups: "https://groups.roblox.com".to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads a config file, resolving its relative paths against the file's directory.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read config '{}': {}", path.display(), e))?;
+        let config: Config = toml::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Invalid config '{}': {}", path.display(), e))?;
+
+        match path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            Some(dir) => Ok(config.relative_to(dir)),
+            None => Ok(config),
+        }
+    }
+
+    /// Makes the relative file paths relative to `dir` instead of the current directory.
+    pub fn relative_to(mut self, dir: &Path) -> Self {
+        for path in [
+            &mut self.output,
+            &mut self.mapping_file,
+            &mut self.place_cache_file,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            let expanded = shellexpand::tilde(path.as_str()).to_string();
+            if Path::new(&expanded).is_relative() {
+                *path = dir.join(expanded).to_string_lossy().into_owned();
+            }
+        }
+        self
+    }
+
+    /// Loads `animation-replacer.toml` from the current directory, if there is one.
+    pub fn discover() -> anyhow::Result<Option<(PathBuf, Self)>> {
+        let path = PathBuf::from(CONFIG_FILE_NAME);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let config = Self::load(&path)?;
+        Ok(Some((path, config)))
+    }
+}
+
+impl RetryConfig {
+    /// Applies the set fields on top of a policy.
+    pub fn apply(&self, mut policy: RetryPolicy) -> RetryPolicy {
+        if let Some(max_attempts) = self.max_attempts {
+            policy.max_attempts = max_attempts;
+        }
+        if let Some(base_delay_ms) = self.base_delay_ms {
+            policy.base_delay = Duration::from_millis(base_delay_ms);
+        }
+        if let Some(max_delay_ms) = self.max_delay_ms {
+            policy.max_delay = Duration::from_millis(max_delay_ms);
+        }
+        if let Some(timeout_secs) = self.timeout_secs {
+            policy.timeout = Duration::from_secs(timeout_secs);
+        }
+        if let Some(timeout_growth_secs) = self.timeout_growth_secs {
+            policy.timeout_growth = Duration::from_secs(timeout_growth_secs);
+        }
+        if let Some(retry_on) = &self.retry_on {
+            policy.retryable = retry_on.iter().copied().collect();
+        }
+        policy
+    }
+}
+
+impl FilterConfig {
+    /// Builds the filter described by the table.
+    pub fn to_filter(&self) -> anyhow::Result<AnimationFilter> {
+        AnimationFilter::new()
+            .allow_ids(self.only_ids.iter().copied())
+            .deny_ids(self.skip_ids.iter().copied())
+            .allow_creators(self.only_creators.iter().copied())
+            .deny_creators(self.skip_creators.iter().copied())
+            .allow_scripts(self.only_scripts.iter().cloned())
+            .deny_scripts(self.skip_scripts.iter().cloned())
+            .include_paths(&self.include_paths)
+            .and_then(|filter| filter.exclude_paths(&self.exclude_paths))
+    }
+}
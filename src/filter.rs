@@ -0,0 +1,171 @@
+use crate::Creator;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::collections::HashSet;
+
+/// Decides which animations a run is allowed to touch.
+///
+/// Every rule is optional. Allow lists are ignored while empty, deny lists always win.
+/// The same filter is used when scanning (workspace_animations, all_animations_in_scripts)
+/// and when writing the mapping back (update_game_animations, update_script_animations).
+///
+/// # Examples
+///
+/// ```rust
+/// let filter = AnimationFilter::new()
+///     .deny_ids([507766388])
+///     .include_paths(["ReplicatedStorage/Weapons/**"])?;
+///
+/// let parser = StudioParser::builder()
+///     .file_path("MyPlace.rbxl")
+///     .filter(filter)
+///     .build()?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AnimationFilter {
+    pub allowed_ids: HashSet<u64>,
+    pub denied_ids: HashSet<u64>,
+    pub allowed_creators: HashSet<Creator>,
+    pub denied_creators: HashSet<Creator>,
+    pub allowed_scripts: HashSet<String>,
+    pub denied_scripts: HashSet<String>,
+    include_paths: Option<GlobSet>,
+    exclude_paths: Option<GlobSet>,
+}
+
+impl AnimationFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only reupload these asset IDs.
+    pub fn allow_ids<I: IntoIterator<Item = u64>>(mut self, ids: I) -> Self {
+        self.allowed_ids.extend(ids);
+        self
+    }
+
+    /// Never reupload these asset IDs.
+    pub fn deny_ids<I: IntoIterator<Item = u64>>(mut self, ids: I) -> Self {
+        self.denied_ids.extend(ids);
+        self
+    }
+
+    /// Only reupload animations created by these users or groups.
+    pub fn allow_creators<I: IntoIterator<Item = Creator>>(mut self, creators: I) -> Self {
+        self.allowed_creators.extend(creators);
+        self
+    }
+
+    /// Never reupload animations created by these users or groups.
+    pub fn deny_creators<I: IntoIterator<Item = Creator>>(mut self, creators: I) -> Self {
+        self.denied_creators.extend(creators);
+        self
+    }
+
+    /// Only scan scripts with these names.
+    pub fn allow_scripts<I: IntoIterator<Item = S>, S: Into<String>>(mut self, names: I) -> Self {
+        self.allowed_scripts
+            .extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Never scan scripts with these names.
+    pub fn deny_scripts<I: IntoIterator<Item = S>, S: Into<String>>(mut self, names: I) -> Self {
+        self.denied_scripts
+            .extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Only touch instances whose path matches one of the globs,
+    /// e.g. `ReplicatedStorage/Weapons/**`.
+    pub fn include_paths<I: IntoIterator<Item = S>, S: AsRef<str>>(
+        mut self,
+        globs: I,
+    ) -> anyhow::Result<Self> {
+        self.include_paths = build_glob_set(globs)?;
+        Ok(self)
+    }
+
+    /// Never touch instances whose path matches one of the globs.
+    pub fn exclude_paths<I: IntoIterator<Item = S>, S: AsRef<str>>(
+        mut self,
+        globs: I,
+    ) -> anyhow::Result<Self> {
+        self.exclude_paths = build_glob_set(globs)?;
+        Ok(self)
+    }
+
+    pub fn allows_id(&self, asset_id: u64) -> bool {
+        !self.denied_ids.contains(&asset_id)
+            && (self.allowed_ids.is_empty() || self.allowed_ids.contains(&asset_id))
+    }
+
+    /// Same as `allows_id` for IDs kept as strings, like the keys of an animation mapping.
+    pub fn allows_id_str(&self, asset_id: &str) -> bool {
+        asset_id
+            .parse::<u64>()
+            .is_ok_and(|asset_id| self.allows_id(asset_id))
+    }
+
+    /// Creator rules need an asset info lookup per asset, so callers check this first.
+    pub fn has_creator_rules(&self) -> bool {
+        !self.allowed_creators.is_empty() || !self.denied_creators.is_empty()
+    }
+
+    pub fn allows_creator(&self, creator: Creator) -> bool {
+        !self.denied_creators.contains(&creator)
+            && (self.allowed_creators.is_empty() || self.allowed_creators.contains(&creator))
+    }
+
+    /// Checks an instance path such as `ReplicatedStorage/Weapons/Sword/Slash`.
+    pub fn allows_path(&self, path: &str) -> bool {
+        let included = self
+            .include_paths
+            .as_ref()
+            .is_none_or(|globs| globs.is_match(path));
+        let excluded = self
+            .exclude_paths
+            .as_ref()
+            .is_some_and(|globs| globs.is_match(path));
+
+        included && !excluded
+    }
+
+    /// Script and path rules need each script's full path, so callers check this first.
+    pub fn has_script_rules(&self) -> bool {
+        !self.allowed_scripts.is_empty()
+            || !self.denied_scripts.is_empty()
+            || self.include_paths.is_some()
+            || self.exclude_paths.is_some()
+    }
+
+    /// A script is scanned when both its name and its path are allowed.
+    pub fn allows_script(&self, name: &str, path: &str) -> bool {
+        !self.denied_scripts.contains(name)
+            && (self.allowed_scripts.is_empty() || self.allowed_scripts.contains(name))
+            && self.allows_path(path)
+    }
+}
+
+fn build_glob_set<I: IntoIterator<Item = S>, S: AsRef<str>>(
+    globs: I,
+) -> anyhow::Result<Option<GlobSet>> {
+    let mut builder = GlobSetBuilder::new();
+    let mut is_empty = true;
+
+    for glob in globs {
+        let glob = glob.as_ref();
+        builder.add(
+            GlobBuilder::new(glob)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| anyhow::anyhow!("Invalid path glob '{}': {}", glob, e))?,
+        );
+        is_empty = false;
+    }
+
+    if is_empty {
+        return Ok(None);
+    }
+
+    Ok(Some(builder.build()?))
+}
@@ -0,0 +1,241 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rbx_dom_weak::types::{ContentId, Ref, Variant};
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// First animation ID the generator hands out. Reuploaded IDs follow the original ones.
+pub const FIRST_GENERATED_ID: u64 = 10_000_000_000;
+
+/// Instances per folder, so generated places are a few levels deep like real ones.
+const FOLDER_SIZE: usize = 100;
+
+/// Builds synthetic places with a chosen number of Animation instances, scripts and ID
+/// references, to benchmark parsing, scanning, applying mappings and saving.
+///
+/// * Notes
+/// The same settings and seed always build the same place. Every reference points at one of
+/// `distinct_ids` IDs, and `mapping` maps each of them to a new ID, so applying it touches
+/// every reference.
+///
+/// # Examples
+///
+/// ```rust
+/// let generator = PlaceGenerator::new().animations(10_000).scripts(2_000).seed(7);
+/// generator.write("large.rbxl")?;
+///
+/// let mut parser = StudioParser::builder().file_path("large.rbxl").build()?;
+/// parser.update_game_animations(&generator.mapping());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PlaceGenerator {
+    animations: usize,
+    scripts: usize,
+    references_per_script: usize,
+    script_lines: usize,
+    value_objects: usize,
+    distinct_ids: usize,
+    seed: u64,
+}
+
+impl Default for PlaceGenerator {
+    fn default() -> Self {
+        Self {
+            animations: 1_000,
+            scripts: 200,
+            references_per_script: 5,
+            script_lines: 100,
+            value_objects: 100,
+            distinct_ids: 500,
+            seed: 0,
+        }
+    }
+}
+
+impl PlaceGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how many Animation instances are created under Workspace (default 1000).
+    pub fn animations(mut self, animations: usize) -> Self {
+        self.animations = animations;
+        self
+    }
+
+    /// Sets how many ModuleScripts are created under ReplicatedStorage (default 200).
+    pub fn scripts(mut self, scripts: usize) -> Self {
+        self.scripts = scripts;
+        self
+    }
+
+    /// Sets how many `rbxassetid://` references each script holds (default 5).
+    pub fn references_per_script(mut self, references: usize) -> Self {
+        self.references_per_script = references;
+        self
+    }
+
+    /// Sets how many lines each script has, references included (default 100).
+    pub fn script_lines(mut self, lines: usize) -> Self {
+        self.script_lines = lines;
+        self
+    }
+
+    /// Sets how many StringValues holding an animation ID are created (default 100).
+    pub fn value_objects(mut self, value_objects: usize) -> Self {
+        self.value_objects = value_objects;
+        self
+    }
+
+    /// Sets how many different animation IDs the references are picked from (default 500).
+    pub fn distinct_ids(mut self, distinct_ids: usize) -> Self {
+        self.distinct_ids = distinct_ids.max(1);
+        self
+    }
+
+    /// Sets the seed references are picked with (default 0).
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Every animation ID the generated place can reference, in order.
+    pub fn asset_ids(&self) -> Vec<u64> {
+        (0..self.distinct_ids as u64)
+            .map(|i| FIRST_GENERATED_ID + i)
+            .collect()
+    }
+
+    /// An old -> new mapping covering every generated ID.
+    pub fn mapping(&self) -> HashMap<String, String> {
+        self.asset_ids()
+            .into_iter()
+            .map(|asset_id| {
+                (
+                    asset_id.to_string(),
+                    (asset_id + self.distinct_ids as u64).to_string(),
+                )
+            })
+            .collect()
+    }
+
+    /// Builds the place in memory.
+    pub fn generate(&self) -> WeakDom {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let root = dom.root_ref();
+
+        let workspace = dom.insert(
+            root,
+            InstanceBuilder::new("Workspace").with_name("Workspace"),
+        );
+        let mut folder = workspace;
+        for index in 0..self.animations {
+            if index % FOLDER_SIZE == 0 {
+                folder = insert_folder(&mut dom, workspace, "Rig", index);
+            }
+            let asset_id = self.random_id(&mut rng);
+            dom.insert(
+                folder,
+                InstanceBuilder::new("Animation")
+                    .with_name(format!("Animation{}", index))
+                    .with_property(
+                        "AnimationId",
+                        Variant::ContentId(ContentId::from(format!("rbxassetid://{}", asset_id))),
+                    ),
+            );
+        }
+
+        let storage = dom.insert(
+            root,
+            InstanceBuilder::new("ReplicatedStorage").with_name("ReplicatedStorage"),
+        );
+        for index in 0..self.scripts {
+            if index % FOLDER_SIZE == 0 {
+                folder = insert_folder(&mut dom, storage, "Modules", index);
+            }
+            let source = self.script_source(&mut rng);
+            dom.insert(
+                folder,
+                InstanceBuilder::new("ModuleScript")
+                    .with_name(format!("Module{}", index))
+                    .with_property("Source", Variant::String(source)),
+            );
+        }
+
+        let server_storage = dom.insert(
+            root,
+            InstanceBuilder::new("ServerStorage").with_name("ServerStorage"),
+        );
+        for index in 0..self.value_objects {
+            if index % FOLDER_SIZE == 0 {
+                folder = insert_folder(&mut dom, server_storage, "Config", index);
+            }
+            let asset_id = self.random_id(&mut rng);
+            dom.insert(
+                folder,
+                InstanceBuilder::new("StringValue")
+                    .with_name(format!("Animation{}", index))
+                    .with_property(
+                        "Value",
+                        Variant::String(format!("rbxassetid://{}", asset_id)),
+                    ),
+            );
+        }
+
+        dom
+    }
+
+    /// Builds the place and writes it as a binary `.rbxl`, the format `StudioParser` reads.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let dom = self.generate();
+        let root_children = dom.get_by_ref(dom.root_ref()).unwrap().children();
+
+        let file = File::create(path)
+            .map_err(|e| anyhow::anyhow!("Failed to create '{}': {}", path.display(), e))?;
+        let mut writer = BufWriter::new(file);
+        rbx_binary::to_writer(&mut writer, &dom, root_children)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn random_id(&self, rng: &mut StdRng) -> u64 {
+        FIRST_GENERATED_ID + rng.gen_range(0..self.distinct_ids as u64)
+    }
+
+    /// Filler Luau with the references spread evenly between the lines.
+    fn script_source(&self, rng: &mut StdRng) -> String {
+        let lines = self.script_lines.max(self.references_per_script);
+        let every = lines.checked_div(self.references_per_script);
+
+        let mut source = String::new();
+        for line in 0..lines {
+            if every
+                .is_some_and(|every| line % every == 0 && line / every < self.references_per_script)
+            {
+                source.push_str(&format!(
+                    "local animation{} = \"rbxassetid://{}\"\n",
+                    line,
+                    self.random_id(rng)
+                ));
+            } else {
+                source.push_str(&format!(
+                    "local value{} = math.floor({} * 1.5) -- filler\n",
+                    line, line
+                ));
+            }
+        }
+        source
+    }
+}
+
+fn insert_folder(dom: &mut WeakDom, parent: Ref, prefix: &str, index: usize) -> Ref {
+    dom.insert(
+        parent,
+        InstanceBuilder::new("Folder").with_name(format!("{}{}", prefix, index / FOLDER_SIZE)),
+    )
+}
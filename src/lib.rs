@@ -1,4 +1,17 @@
+use animation::resolver::PlaceResolver;
+use config::Endpoints;
+use progress::Progress;
+use rate_limit::RateLimits;
 use rbx_dom_weak::{Instance, WeakDom};
+use retry::RetryPolicy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use studio::value_ids::SkippedValue;
+use tokio_util::sync::CancellationToken;
 
 /// A module for uploading animations
 pub mod animation;
@@ -9,7 +22,41 @@ pub mod studio;
 /// A module for handling parsing and editing on scripts, in studio files.
 pub mod script;
 
+/// A module for limiting which animations get reuploaded and replaced.
+pub mod filter;
+
+/// A module describing the asset types (animations, sounds, meshes, images) found in places.
+pub mod asset;
+
+/// A module for rate limiting and retrying Roblox API calls.
+pub mod rate_limit;
+
+/// A module describing how failed Roblox API calls are retried.
+pub mod retry;
+
+/// A module for saving and loading old -> new animation ID mappings, used to resume runs.
+pub mod mapping;
+
+/// A module for reporting scan, upload and apply progress as typed events.
+pub mod progress;
+
+/// A module for the `animation-replacer.toml` project configuration file.
+pub mod config;
+
+/// A module for restoring original animation IDs from a saved mapping.
+pub mod revert;
+
+/// A module for remembering which place IDs unlock each creator's assets.
+pub mod places;
+
+/// A module for generating synthetic places to benchmark with.
+#[cfg(feature = "generator")]
+pub mod generator;
+
 pub use animation::uploader::AnimationUploader;
+pub use asset::AssetType;
+pub use filter::AnimationFilter;
+pub use retry::RetryPolicy;
 pub use studio::dom_parser::StudioParserBuilder;
 
 /// Represents an animation with its instance and ID.
@@ -36,8 +83,161 @@ impl<'a> Animation<'a> {
 }
 /// Parser for Roblox Studio files with optional authentication.
 pub struct StudioParser {
-    pub roblosecurity: Option<String>,
+    pub roblosecurity: Option<Roblosecurity>,
     pub dom: WeakDom,
+    /// The file the DOM was loaded from; saving over it makes a backup first.
+    pub file_path: Option<PathBuf>,
+    /// Skip Roblox's default Animate script animations when fetching for reupload.
+    pub exclude_default_animations: bool,
+    pub filter: AnimationFilter,
+    /// Shared by every uploader the parser creates.
+    pub rate_limits: Arc<RateLimits>,
+    /// Passed to every uploader the parser creates.
+    pub retry_policy: RetryPolicy,
+    /// Passed to every uploader the parser creates; cancel it to stop fetching and uploading.
+    pub cancellation: CancellationToken,
+    /// Passed to every uploader the parser creates.
+    pub progress: Progress,
+    /// Passed to every uploader the parser creates.
+    pub endpoints: Endpoints,
+    /// Name template for reuploaded animations, see `AnimationUploader::with_name_template`.
+    pub name_template: String,
+    /// Shared by every uploader the parser creates; save its place cache to reuse places in
+    /// later runs.
+    pub place_resolver: Arc<PlaceResolver>,
+    /// Passed to every uploader the parser creates.
+    pub batch_concurrency: usize,
+    /// Mapped IDs the update functions had to leave in place, see `update_value_animations`.
+    pub skipped_values: Vec<SkippedValue>,
+    /// Old IDs the update functions replaced at least once; `verify_saved_file` only
+    /// expects these to be gone.
+    pub rewritten_ids: HashSet<u64>,
+}
+
+/// The user or group that created an asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Creator {
+    User(u64),
+    Group(u64),
+}
+
+impl TryFrom<String> for Creator {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Creator> for String {
+    fn from(creator: Creator) -> Self {
+        creator.to_string()
+    }
+}
+
+/// Parses `user:123` or `group:456`.
+impl FromStr for Creator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, id) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Expected user:<id> or group:<id>, got '{}'", s))?;
+        let id = id
+            .trim()
+            .parse::<u64>()
+            .map_err(|e| format!("Invalid creator id '{}': {}", id, e))?;
+
+        match kind.trim().to_lowercase().as_str() {
+            "user" => Ok(Creator::User(id)),
+            "group" => Ok(Creator::Group(id)),
+            other => Err(format!("Unknown creator type '{}'", other)),
+        }
+    }
+}
+
+impl fmt::Display for Creator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Creator::User(id) => write!(f, "user:{}", id),
+            Creator::Group(id) => write!(f, "group:{}", id),
+        }
+    }
+}
+
+/// A .ROBLOSECURITY cookie. Debug and Display print `<redacted>`, so the cookie can't leak
+/// into logs or error messages by accident; `expose` is the only way to read it.
+///
+/// # Examples
+///
+/// ```rust
+/// let cookie = Roblosecurity::new(std::env::var("ROBLOSECURITY")?);
+/// println!("{:?}", cookie); // Roblosecurity(<redacted>)
+/// let client = ClientBuilder::new().roblosecurity(cookie.expose().to_string()).build();
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct Roblosecurity(String);
+
+impl Roblosecurity {
+    /// Wraps a cookie, trimming surrounding whitespace and a `.ROBLOSECURITY=` prefix
+    /// copied along from the browser.
+    pub fn new<S: Into<String>>(cookie: S) -> Self {
+        let cookie = cookie.into();
+        let trimmed = cookie.trim();
+        let trimmed = trimmed.strip_prefix(".ROBLOSECURITY=").unwrap_or(trimmed);
+        Self(trimmed.to_string())
+    }
+
+    /// Reads a cookie from a file. Only the path ends up in the error.
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            anyhow::anyhow!("Failed to read cookie file '{}': {}", path.display(), e)
+        })?;
+        Ok(Self::new(contents))
+    }
+
+    /// The raw cookie, for building API clients. Never log it.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Replaces any occurrence of the cookie in a message, e.g. an error echoing a request.
+    pub fn redact(&self, message: &str) -> String {
+        if self.0.is_empty() {
+            return message.to_string();
+        }
+        message.replace(&self.0, "<redacted>")
+    }
+}
+
+impl fmt::Debug for Roblosecurity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Roblosecurity(<redacted>)")
+    }
+}
+
+impl fmt::Display for Roblosecurity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl From<String> for Roblosecurity {
+    fn from(cookie: String) -> Self {
+        Self::new(cookie)
+    }
+}
+
+impl From<&str> for Roblosecurity {
+    fn from(cookie: &str) -> Self {
+        Self::new(cookie)
+    }
 }
 
 /// Represents a script with its instance, source code and type.
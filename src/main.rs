@@ -1,20 +1,49 @@
 use animation_replace_roblox::StudioParser;
-use animation_replace_roblox::animation::uploader::AnimationUploader;
-use clap::Parser;
-use roboat::assetdelivery::AssetBatchResponse;
+use animation_replace_roblox::animation::pipeline::PIPELINE_CAPACITY;
+use animation_replace_roblox::config::{Config, FilterConfig};
+use animation_replace_roblox::mapping::{MappingDocument, invert_mapping, load_mapping};
+use animation_replace_roblox::places::PlaceCache;
+use animation_replace_roblox::progress::{ApplyTarget, Progress, ProgressEvent, ScanSource};
+use animation_replace_roblox::retry::ErrorClass;
+use animation_replace_roblox::revert::revert_rojo_tree;
+use animation_replace_roblox::studio::inventory::{inventory_to_csv, inventory_to_json};
+use animation_replace_roblox::{AnimationFilter, AssetType, Creator, RetryPolicy, Roblosecurity};
+use clap::{Args as ClapArgs, Parser, Subcommand, ValueEnum};
+use indicatif::{ProgressBar, ProgressStyle};
+use log::kv::{Key, Value, VisitSource};
+use log::{LevelFilter, error, info, warn};
+use std::collections::HashSet;
+use std::io::Write;
+use std::process::ExitCode;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, UnboundedReceiver, unbounded_channel};
+use tokio_util::sync::CancellationToken;
+
+/// Least time between saves of the mapping file while uploads finish.
+const MAPPING_SAVE_INTERVAL: Duration = Duration::from_secs(5);
 
 #[derive(Parser, Debug)]
+#[command(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
 struct Args {
-    /// .ROBLOSECURITY cookie string [WARNING STRING REQUIRED]
-    #[arg(long, short)]
-    cookie: String,
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    /// file PATH of the .rbxl file [REQUIRED]
-    #[arg(long, short)]
-    file: String,
+    #[command(flatten)]
+    cookie: CookieArgs,
 
-    /// Save the copy instead replacing file [AVOID DATA LOSS]
+    /// Project config file; flags given on the command line override it
+    /// [defaulted to animation-replacer.toml in the current directory, if present]
+    #[arg(long)]
+    config: Option<String>,
+
+    /// file PATH of the .rbxl file, or a glob like "places/*.rbxl". Repeat it to process
+    /// several places with one shared set of uploads [REQUIRED]
+    #[arg(long, short, required = true, num_args = 1..)]
+    file: Vec<String>,
+
+    /// Save the copy instead replacing file [AVOID DATA LOSS]. With several files this is a
+    /// directory each file is saved into
     #[arg(long, short)]
     output: Option<String>,
 
@@ -25,71 +54,1093 @@ struct Args {
     /// How many concurrent tasks using semaphore. [defaulted to 5]
     #[arg(long, short)]
     threads: Option<u64>,
+
+    /// How many batches of 250 animation IDs are fetched at once [defaulted to 4]
+    #[arg(long)]
+    batch_concurrency: Option<usize>,
+
+    /// Don't reupload Roblox's default Animate script animations (idle, walk, run...)
+    #[arg(long, overrides_with = "no_skip_default_animations")]
+    skip_default_animations: bool,
+
+    /// Reupload Roblox's default animations even when the config skips them
+    #[arg(long, overrides_with = "skip_default_animations")]
+    no_skip_default_animations: bool,
+
+    /// Only reupload these animation IDs [comma separated]
+    #[arg(long, value_delimiter = ',')]
+    only_ids: Vec<u64>,
+
+    /// Never reupload these animation IDs [comma separated]
+    #[arg(long, value_delimiter = ',')]
+    skip_ids: Vec<u64>,
+
+    /// Only reupload animations made by these creators [user:ID or group:ID, comma separated]
+    #[arg(long, value_delimiter = ',')]
+    only_creators: Vec<Creator>,
+
+    /// Never reupload animations made by these creators [user:ID or group:ID, comma separated]
+    #[arg(long, value_delimiter = ',')]
+    skip_creators: Vec<Creator>,
+
+    /// Only touch instances matching this path glob, e.g. "ReplicatedStorage/Weapons/**"
+    #[arg(long)]
+    include_path: Vec<String>,
+
+    /// Never touch instances matching this path glob
+    #[arg(long)]
+    exclude_path: Vec<String>,
+
+    /// Only scan scripts with these names [comma separated]
+    #[arg(long, value_delimiter = ',')]
+    only_scripts: Vec<String>,
+
+    /// Never scan scripts with these names [comma separated]
+    #[arg(long, value_delimiter = ',')]
+    skip_scripts: Vec<String>,
+
+    /// Asset type to process: animation, audio, mesh or image. Only animations are
+    /// reuploaded, other types are reported. [defaulted to animation]
+    #[arg(long)]
+    asset_type: Option<AssetType>,
+
+    /// Name of reuploaded animations; {id} is the original ID and {name} the original
+    /// instance name, e.g. "{name} ({id})" [defaulted to reuploaded_animation]
+    #[arg(long)]
+    name_template: Option<String>,
+
+    /// Show debug logs, e.g. every metadata response and place lookup
+    #[arg(long, short, global = true, conflicts_with = "quiet")]
+    verbose: bool,
+
+    /// Only show errors, and hide the progress bar
+    #[arg(long, short, global = true)]
+    quiet: bool,
+
+    /// Log output format; json prints one object per line and hides the progress bar
+    /// [defaulted to text]
+    #[arg(long, value_enum, global = true, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// Don't check the cookie and group permissions before starting
+    #[arg(long)]
+    skip_preflight: bool,
+
+    /// JSON file of old -> new animation IDs. Read at start so already reuploaded animations
+    /// are skipped, and written at the end (also after Ctrl-C) so the run can be resumed
+    #[arg(long)]
+    mapping_file: Option<String>,
+
+    /// JSON file remembering which place ID unlocks each creator's assets, reused by later
+    /// runs so games don't have to be listed again
+    #[arg(long)]
+    place_cache_file: Option<String>,
+
+    /// Attempts per request, including the first one [defaulted to 6]
+    #[arg(long)]
+    max_attempts: Option<u32>,
+
+    /// Delay before the first retry, doubled after each retry [defaulted to 500]
+    #[arg(long)]
+    retry_base_delay_ms: Option<u64>,
+
+    /// Longest delay between retries [defaulted to 30000]
+    #[arg(long)]
+    retry_max_delay_ms: Option<u64>,
+
+    /// Timeout of the first attempt of each request [defaulted to 10]
+    #[arg(long)]
+    request_timeout_secs: Option<u64>,
+
+    /// Added to the timeout on every retry [defaulted to 5]
+    #[arg(long)]
+    timeout_growth_secs: Option<u64>,
+
+    /// Error classes worth retrying: rate-limited, timeout, network, server-error,
+    /// malformed-response [comma separated, defaulted to all]
+    #[arg(long, value_delimiter = ',')]
+    retry_on: Vec<ErrorClass>,
 }
 
-#[tokio::main]
-async fn main() {
-    let args = Args::parse();
-    let file_path = shellexpand::tilde(&args.file).to_string();
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Lists every asset ID referenced in a place (read only)
+    Inventory {
+        /// file PATH of the .rbxl file [REQUIRED]
+        #[arg(long, short)]
+        file: String,
 
-    // Build the parser with the roboat client
-    let builder = StudioParser::builder()
-        .file_path(&file_path)
-        .roblosecurity(&args.cookie);
+        /// A cookie adds the asset type and creator of each ID [OPTIONAL]
+        #[command(flatten)]
+        cookie: CookieArgs,
+
+        /// Export format [defaulted to json]
+        #[arg(long, value_enum, default_value_t = InventoryFormat::Json)]
+        format: InventoryFormat,
+
+        /// Write the export to a file instead of printing it
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+    /// Lists the animation IDs that differ between two places, with script source diffs
+    Diff {
+        /// The place before the changes
+        before: String,
+
+        /// The place after the changes
+        after: String,
+    },
+    /// Restores the original animation IDs from a saved mapping
+    Revert {
+        /// PATH of the .rbxl place, .rbxm model or Rojo project directory to restore
+        #[arg(long, short)]
+        file: String,
+
+        /// The old -> new mapping saved by --mapping-file during the reupload
+        #[arg(long)]
+        mapping_file: String,
+
+        /// Where to save a restored place or model [defaulted to overwriting it, after a backup]
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+    /// Merges mapping files from several runs or team members into one
+    MergeMappings {
+        /// Mapping files to merge; when they disagree the most recent upload wins
+        #[arg(required = true, num_args = 2..)]
+        files: Vec<String>,
+
+        /// Where to save the merged mapping
+        #[arg(long, short)]
+        output: String,
+    },
+}
+
+/// Where to read the .ROBLOSECURITY cookie from. Prefer the environment variable, a file or
+/// stdin: --cookie ends up in shell history and process listings.
+#[derive(ClapArgs, Debug)]
+struct CookieArgs {
+    /// .ROBLOSECURITY cookie string [REQUIRED unless given another way]
+    #[arg(long, short, env = "ROBLOSECURITY", hide_env_values = true)]
+    cookie: Option<String>,
+
+    /// Read the cookie from a file [takes priority over --cookie]
+    #[arg(long)]
+    cookie_file: Option<String>,
+
+    /// Read the cookie from the first line of stdin [takes priority over --cookie-file]
+    #[arg(long)]
+    cookie_stdin: bool,
+}
+
+impl CookieArgs {
+    fn read(&self) -> anyhow::Result<Option<Roblosecurity>> {
+        let cookie = if self.cookie_stdin {
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line)?;
+            Some(Roblosecurity::new(line))
+        } else if let Some(cookie_file) = &self.cookie_file {
+            Some(Roblosecurity::from_file(
+                shellexpand::tilde(cookie_file).to_string(),
+            )?)
+        } else {
+            self.cookie.as_deref().map(Roblosecurity::new)
+        };
+
+        Ok(cookie.filter(|cookie| !cookie.is_empty()))
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum InventoryFormat {
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum LogFormat {
+    Text,
+    Json,
+}
+
+/// Sets up the logger the library logs through. `RUST_LOG` still overrides the level.
+fn init_logging(verbose: bool, quiet: bool, format: LogFormat) {
+    let level = if quiet {
+        LevelFilter::Error
+    } else if verbose {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Info
+    };
+
+    let mut builder = env_logger::Builder::new();
+    builder.filter_level(level).parse_default_env();
+
+    if format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let mut fields = serde_json::Map::new();
+            fields.insert("level".into(), record.level().as_str().into());
+            fields.insert("target".into(), record.target().into());
+            fields.insert("message".into(), record.args().to_string().into());
+            let _ = record.key_values().visit(&mut JsonFields(&mut fields));
+            writeln!(buf, "{}", serde_json::Value::Object(fields))
+        });
+    }
+
+    builder.init();
+}
+
+/// Copies a log record's key-values (asset_id, place_id, attempt...) into a JSON object.
+struct JsonFields<'a>(&'a mut serde_json::Map<String, serde_json::Value>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+        let value = match value.to_u64() {
+            Some(number) => number.into(),
+            None => value.to_string().into(),
+        };
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+/// Loads `--config`, or `animation-replacer.toml` from the current directory when it exists.
+fn load_config(path: Option<&str>) -> anyhow::Result<Config> {
+    match path {
+        Some(path) => Config::load(shellexpand::tilde(path).to_string()),
+        None => match Config::discover()? {
+            Some((path, config)) => {
+                info!("Using {}", path.display());
+                Ok(config)
+            }
+            None => Ok(Config::default()),
+        },
+    }
+}
+
+/// Expands `~` and globs in the --file arguments, keeping the given order.
+fn expand_files(patterns: &[String]) -> anyhow::Result<Vec<String>> {
+    let mut files = Vec::new();
+    for pattern in patterns {
+        let pattern = shellexpand::tilde(pattern).to_string();
+        if !pattern.contains(['*', '?', '[']) {
+            files.push(pattern);
+            continue;
+        }
+
+        let matches = glob::glob(&pattern)
+            .map_err(|e| anyhow::anyhow!("Invalid file glob '{}': {}", pattern, e))?;
+        for path in matches {
+            let path = path?;
+            files.push(path.to_string_lossy().to_string());
+        }
+    }
+
+    let mut seen = HashSet::new();
+    files.retain(|file| seen.insert(file.clone()));
+    Ok(files)
+}
+
+/// Where a processed place is written: the original file, --output, or a file of the same
+/// name inside the --output directory when several places are processed.
+fn output_path(file_path: &str, output: Option<&str>, many_files: bool) -> anyhow::Result<String> {
+    let Some(output) = output else {
+        return Ok(file_path.to_string());
+    };
+    let output = shellexpand::tilde(output).to_string();
+    if !many_files {
+        return Ok(output);
+    }
+
+    std::fs::create_dir_all(&output)
+        .map_err(|e| anyhow::anyhow!("Failed to create output directory '{}': {}", output, e))?;
+    let file_name = std::path::Path::new(file_path)
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("'{}' has no file name", file_path))?;
+    Ok(std::path::Path::new(&output)
+        .join(file_name)
+        .to_string_lossy()
+        .to_string())
+}
+
+/// CLI list flags replace the config's list instead of adding to it.
+fn cli_or_config<T: Clone>(cli: &[T], config: &[T]) -> Vec<T> {
+    if cli.is_empty() {
+        config.to_vec()
+    } else {
+        cli.to_vec()
+    }
+}
+
+/// Prints the animation references that differ between two places.
+fn diff(before: String, after: String) -> ExitCode {
+    let load = |file: &str| {
+        StudioParser::builder()
+            .file_path(shellexpand::tilde(file).to_string())
+            .build()
+    };
+    let (before_parser, after_parser) = match (load(&before), load(&after)) {
+        (Ok(before_parser), Ok(after_parser)) => (before_parser, after_parser),
+        (Err(e), _) | (_, Err(e)) => {
+            error!("Error loading file: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let place_diff = before_parser.diff_animations(&after_parser);
+    if place_diff.is_empty() {
+        println!(
+            "No animation references differ between {} and {}",
+            before, after
+        );
+        return ExitCode::SUCCESS;
+    }
+
+    let format_ids = |ids: &[u64]| match ids {
+        [] => "-".to_string(),
+        ids => ids
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+
+    if !place_diff.references.is_empty() {
+        println!("Instances ({}):", place_diff.references.len());
+        for change in &place_diff.references {
+            println!(
+                "  {} [{}] {}: {} -> {}",
+                change.path,
+                change.class,
+                change.property,
+                format_ids(&change.old_ids),
+                format_ids(&change.new_ids)
+            );
+        }
+    }
+
+    if !place_diff.scripts.is_empty() {
+        println!("Scripts ({}):", place_diff.scripts.len());
+        for change in &place_diff.scripts {
+            println!(
+                "  {}: {} -> {}",
+                change.path,
+                format_ids(&change.old_ids),
+                format_ids(&change.new_ids)
+            );
+        }
+        for change in &place_diff.scripts {
+            println!();
+            print!("{}", change.diff);
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// Puts the original animation IDs back into a place, model or Rojo tree.
+fn revert(file: String, mapping_file: String, output: Option<String>) -> ExitCode {
+    let mapping_file = shellexpand::tilde(&mapping_file).to_string();
+    if !std::path::Path::new(&mapping_file).exists() {
+        error!("Mapping file {} doesn't exist", mapping_file);
+        return ExitCode::FAILURE;
+    }
+    let animation_mapping = match load_mapping(&mapping_file) {
+        Ok(animation_mapping) => animation_mapping,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let file = shellexpand::tilde(&file).to_string();
+    let report = if std::path::Path::new(&file).is_dir() {
+        match revert_rojo_tree(&file, &animation_mapping, &AnimationFilter::new()) {
+            Ok(report) => report,
+            Err(e) => {
+                error!("Failed to revert Rojo tree {}: {}", file, e);
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        let mut parser = match StudioParser::builder().file_path(&file).build() {
+            Ok(parser) => parser,
+            Err(e) => {
+                error!("Error loading file: {}", e);
+                return ExitCode::FAILURE;
+            }
+        };
+
+        let report = match parser.revert_animations(&animation_mapping) {
+            Ok(report) => report,
+            Err(e) => {
+                error!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        };
+
+        let inverted = match invert_mapping(&animation_mapping) {
+            Ok(inverted) => inverted,
+            Err(e) => {
+                error!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        };
+        let target = output.unwrap_or_else(|| file.clone());
+        if let Err(e) = parser.save_verified(&target, Some(&inverted)) {
+            error!("Failed to save {}: {}", target, e);
+            return ExitCode::FAILURE;
+        }
+        report
+    };
+
+    for (new_id, original_id) in &report.restored {
+        println!("  {} -> {}", new_id, original_id);
+    }
+    for path in &report.files {
+        println!("  restored {}", path.display());
+    }
+    println!(
+        "Restored {} animation IDs ({} instance references, {} scripts)",
+        report.restored.len(),
+        report.instances,
+        report.scripts
+    );
+    ExitCode::SUCCESS
+}
+
+/// Combines mapping files into one document, reporting animations uploaded twice.
+fn merge_mappings(files: Vec<String>, output: String) -> ExitCode {
+    let mut merged = MappingDocument::new();
+    for file in &files {
+        let file = shellexpand::tilde(file).to_string();
+        if !std::path::Path::new(&file).exists() {
+            error!("Mapping file {} doesn't exist", file);
+            return ExitCode::FAILURE;
+        }
+        let document = match MappingDocument::load(&file) {
+            Ok(document) => document,
+            Err(e) => {
+                error!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        };
+        for conflict in merged.merge(document) {
+            warn!(
+                old_id = conflict.old_id.as_str(), kept = conflict.kept.as_str(), discarded = conflict.discarded.as_str();
+                "{}: animation {} was reuploaded as both {} and {}, keeping {}",
+                file, conflict.old_id, conflict.kept, conflict.discarded, conflict.kept
+            );
+        }
+    }
+
+    let output = shellexpand::tilde(&output).to_string();
+    if let Err(e) = merged.save(&output) {
+        error!("{}", e);
+        return ExitCode::FAILURE;
+    }
+    println!(
+        "Merged {} mappings into {} ({} animations)",
+        files.len(),
+        output,
+        merged.entries.len()
+    );
+    ExitCode::SUCCESS
+}
+
+/// Exports every asset ID referenced in a place as JSON or CSV.
+async fn inventory(
+    file: String,
+    cookie: CookieArgs,
+    format: InventoryFormat,
+    output: Option<String>,
+) -> ExitCode {
+    let cookie = match cookie.read() {
+        Ok(cookie) => cookie,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
 
-    let mut parser = match builder.build() {
+    let mut builder = StudioParser::builder().file_path(shellexpand::tilde(&file).to_string());
+    if let Some(cookie) = &cookie {
+        builder = builder.roblosecurity(cookie.clone());
+    }
+
+    let parser = match builder.build() {
         Ok(parser) => parser,
         Err(e) => {
-            eprintln!("Error loading file: {}", e);
-            return;
+            error!("Error loading file: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut entries = parser.asset_inventory();
+    if cookie.is_some() {
+        let annotated = parser.annotate_inventory(&mut entries).await;
+        if let Err(e) = annotated {
+            error!("Failed to fetch asset metadata: {:?}", e);
         }
+    }
+
+    let export = match format {
+        InventoryFormat::Json => match inventory_to_json(&entries) {
+            Ok(json) => json,
+            Err(e) => {
+                error!("Failed to serialize inventory: {}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+        InventoryFormat::Csv => inventory_to_csv(&entries),
     };
 
-    let mut all_animations: Vec<AssetBatchResponse> = Vec::new();
-    let workspace_animations = parser.workspace_animations();
-    match workspace_animations.await {
-        Ok(mut animations) => {
-            // println!("Animations: {:?}", animations);
-            all_animations.append(&mut animations);
+    match output {
+        Some(output) => {
+            let output = shellexpand::tilde(&output).to_string();
+            if let Err(e) = std::fs::write(&output, export) {
+                error!("Failed to write inventory to {}: {}", output, e);
+                return ExitCode::FAILURE;
+            }
+        }
+        None => println!("{}", export),
+    }
+    ExitCode::SUCCESS
+}
+
+/// Renders progress events as a live progress bar until every sender is dropped.
+async fn render_progress(mut receiver: UnboundedReceiver<ProgressEvent>, hidden: bool) {
+    let bar = if hidden {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(0)
+    };
+    bar.set_style(
+        ProgressStyle::with_template("[{elapsed_precise}] {bar:40} {pos}/{len} {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar()),
+    );
+
+    while let Some(event) = receiver.recv().await {
+        match event {
+            ProgressEvent::ScanFound {
+                source,
+                asset_type,
+                count,
+            } => {
+                let source = match source {
+                    ScanSource::Workspace => "the workspace",
+                    ScanSource::Scripts => "scripts",
+                };
+                bar.println(format!("Found {} {} IDs in {}", count, asset_type, source));
+            }
+            ProgressEvent::BatchFetched { requested, fetched } => {
+                bar.set_message(format!("fetched {}/{} assets", fetched, requested));
+            }
+            ProgressEvent::UploadsQueued { count } => bar.inc_length(count as u64),
+            ProgressEvent::DownloadStarted { asset_id } => {
+                bar.set_message(format!("downloading {}", asset_id));
+            }
+            ProgressEvent::DownloadFinished { asset_id } => {
+                bar.set_message(format!("uploading {}", asset_id));
+            }
+            ProgressEvent::UploadSucceeded { .. } => bar.inc(1),
+            ProgressEvent::UploadFailed { asset_id, .. } => {
+                bar.inc(1);
+                bar.set_message(format!("failed {}", asset_id));
+            }
+            ProgressEvent::ApplyFinished { target, updated } => {
+                let target = match target {
+                    ApplyTarget::Scripts => "scripts",
+                    ApplyTarget::Instances => "instance properties",
+                };
+                bar.println(format!("Updated {} {}", updated, target));
+            }
+        }
+    }
+
+    bar.finish_and_clear();
+}
+
+/// Prints every reference to an asset type that has no uploader yet.
+async fn report_assets(parser: &StudioParser, asset_type: AssetType) {
+    let references = parser.asset_references(asset_type);
+    for reference in &references {
+        println!(
+            "{}.{} -> {}",
+            parser.instance_path(reference.referent),
+            reference.property,
+            reference.asset_id
+        );
+    }
+
+    let mut script_references = 0;
+    match parser.all_assets_in_scripts(asset_type).await {
+        Ok(responses) => {
+            for request_id in responses.into_iter().filter_map(|r| r.request_id) {
+                println!("(script) -> {}", request_id);
+                script_references += 1;
+            }
         }
         Err(e) => {
-            eprintln!("Failed to workspace animations: {:?}", e);
+            error!("Failed to fetch {} assets in scripts: {:?}", asset_type, e);
         }
     }
 
-    let script_animations = parser.all_animations_in_scripts();
+    println!(
+        "Found {} {} references in instances and {} in scripts. Reuploading {} is not supported yet.",
+        references.len(),
+        asset_type,
+        script_references,
+        asset_type
+    );
+}
 
-    match script_animations.await {
-        Ok(mut animations) => {
-            // println!("Animations: {:?}", animations);
-            all_animations.append(&mut animations);
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+    init_logging(args.verbose, args.quiet, args.log_format);
+
+    if let Some(command) = args.command {
+        return match command {
+            Command::Inventory {
+                file,
+                cookie,
+                format,
+                output,
+            } => inventory(file, cookie, format, output).await,
+            Command::Diff { before, after } => diff(before, after),
+            Command::Revert {
+                file,
+                mapping_file,
+                output,
+            } => revert(file, mapping_file, output),
+            Command::MergeMappings { files, output } => merge_mappings(files, output),
+        };
+    }
+
+    // clap only skips these requirements when a subcommand is given
+    let file_paths = match expand_files(&args.file) {
+        Ok(file_paths) if !file_paths.is_empty() => file_paths,
+        Ok(_) => {
+            error!("--file is required and must match at least one file");
+            return ExitCode::FAILURE;
+        }
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let cookie = match args.cookie.read() {
+        Ok(Some(cookie)) => cookie,
+        Ok(None) => {
+            error!(
+                "A cookie is required: set ROBLOSECURITY, or use --cookie-file, --cookie-stdin or --cookie"
+            );
+            return ExitCode::FAILURE;
         }
         Err(e) => {
-            eprintln!("Failed to fetch animations: {:?}", e);
+            error!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let config = match load_config(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Flags given on the command line win over the config file
+    let group = args.group.or(config.group);
+    let threads = args.threads.or(config.threads);
+    let batch_concurrency = args.batch_concurrency.or(config.batch_concurrency);
+    let output = args.output.clone().or(config.output.clone());
+    let mapping_file = args.mapping_file.clone().or(config.mapping_file.clone());
+    let place_cache_file = args
+        .place_cache_file
+        .clone()
+        .or(config.place_cache_file.clone());
+    let asset_type = args
+        .asset_type
+        .or(config.asset_type)
+        .unwrap_or(AssetType::Animation);
+    let skip_default_animations = match (
+        args.skip_default_animations,
+        args.no_skip_default_animations,
+    ) {
+        (true, _) => true,
+        (_, true) => false,
+        _ => config.skip_default_animations.unwrap_or(false),
+    };
+
+    let filters = FilterConfig {
+        only_ids: cli_or_config(&args.only_ids, &config.filters.only_ids),
+        skip_ids: cli_or_config(&args.skip_ids, &config.filters.skip_ids),
+        only_creators: cli_or_config(&args.only_creators, &config.filters.only_creators),
+        skip_creators: cli_or_config(&args.skip_creators, &config.filters.skip_creators),
+        include_paths: cli_or_config(&args.include_path, &config.filters.include_paths),
+        exclude_paths: cli_or_config(&args.exclude_path, &config.filters.exclude_paths),
+        only_scripts: cli_or_config(&args.only_scripts, &config.filters.only_scripts),
+        skip_scripts: cli_or_config(&args.skip_scripts, &config.filters.skip_scripts),
+    };
+    let filter = match filters.to_filter() {
+        Ok(filter) => filter,
+        Err(e) => {
+            error!("Invalid filter: {}", e);
+            return ExitCode::FAILURE;
         }
+    };
+
+    let mut retry_policy = config.retry.apply(RetryPolicy::default());
+    if let Some(max_attempts) = args.max_attempts {
+        retry_policy.max_attempts = max_attempts;
+    }
+    if let Some(base_delay_ms) = args.retry_base_delay_ms {
+        retry_policy.base_delay = Duration::from_millis(base_delay_ms);
+    }
+    if let Some(max_delay_ms) = args.retry_max_delay_ms {
+        retry_policy.max_delay = Duration::from_millis(max_delay_ms);
+    }
+    if let Some(timeout_secs) = args.request_timeout_secs {
+        retry_policy.timeout = Duration::from_secs(timeout_secs);
+    }
+    if let Some(timeout_growth_secs) = args.timeout_growth_secs {
+        retry_policy.timeout_growth = Duration::from_secs(timeout_growth_secs);
+    }
+    if !args.retry_on.is_empty() {
+        retry_policy.retryable = args.retry_on.iter().copied().collect();
     }
 
-    let uploader = Arc::new(AnimationUploader::new(args.cookie));
-    match uploader
-        .reupload_all_animations(all_animations, args.group.clone(), args.threads.clone())
+    let mapping_document = match &mapping_file {
+        Some(mapping_file) => {
+            match MappingDocument::load(shellexpand::tilde(mapping_file).to_string()) {
+                Ok(document) => document,
+                Err(e) => {
+                    error!("{}", e);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        None => MappingDocument::new(),
+    };
+    let mut animation_mapping = mapping_document.animation_mapping();
+
+    let place_cache = match &place_cache_file {
+        Some(place_cache_file) => {
+            match PlaceCache::load(shellexpand::tilde(place_cache_file).to_string()) {
+                Ok(place_cache) => place_cache,
+                Err(e) => {
+                    error!("{}", e);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        None => PlaceCache::new(),
+    };
+
+    // Ctrl-C stops fetching and new uploads; whatever finished is still applied and saved
+    let cancellation = CancellationToken::new();
+    let ctrl_c_token = cancellation.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            warn!("Interrupted, waiting for in-flight uploads before saving...");
+            ctrl_c_token.cancel();
+        }
+    });
+
+    let (progress_sender, progress_receiver) = unbounded_channel();
+    let hide_progress = args.quiet || args.log_format == LogFormat::Json;
+    let renderer = tokio::spawn(render_progress(progress_receiver, hide_progress));
+
+    // Build the parser with the roboat client
+    let mut builder = StudioParser::builder()
+        .roblosecurity(cookie)
+        .exclude_default_animations(skip_default_animations)
+        .filter(filter)
+        .retry_policy(retry_policy)
+        .endpoints(config.endpoints.clone())
+        .cancellation(cancellation.clone())
+        .place_cache(place_cache.with_overrides(config.places.clone()))
+        .progress(progress_sender);
+
+    let name_template = args.name_template.clone().or(config.name_template.clone());
+    if let Some(name_template) = &name_template {
+        builder = builder.name_template(name_template);
+    }
+    if let Some(batch_concurrency) = batch_concurrency {
+        builder = builder.batch_concurrency(batch_concurrency);
+    }
+
+    // Clones of the builder share its rate limiters and place resolver, so the limits,
+    // caches and the filter apply to the whole run
+    let mut parsers = Vec::with_capacity(file_paths.len());
+    for file_path in &file_paths {
+        match builder.clone().file_path(file_path).build() {
+            Ok(parser) => parsers.push(parser),
+            Err(e) => {
+                error!("Error loading file: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    // The builder holds a progress sender too; only the parsers should keep the bar alive
+    drop(builder);
+    let Some(first_parser) = parsers.first() else {
+        return ExitCode::SUCCESS;
+    };
+
+    if !args.skip_preflight {
+        let preflight = match first_parser.animation_uploader() {
+            Ok(uploader) => uploader.preflight(group).await,
+            Err(e) => Err(e.into()),
+        };
+
+        match preflight {
+            Ok(preflight) => match preflight.group {
+                Some(group) => info!(
+                    "Authenticated as user {}, {} (rank {}) in group {}",
+                    preflight.user_id, group.role, group.rank, group.group_id
+                ),
+                None => info!("Authenticated as user {}", preflight.user_id),
+            },
+            Err(e) => {
+                error!("Preflight check failed: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    if !asset_type.can_reupload() {
+        for (file_path, parser) in file_paths.iter().zip(&parsers) {
+            info!("{}:", file_path);
+            report_assets(parser, asset_type).await;
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    // Collect the IDs of every place first so shared animations are fetched and uploaded once
+    let mut animation_ids: Vec<u64> = Vec::new();
+    let mut file_animation_ids: Vec<HashSet<String>> = Vec::with_capacity(parsers.len());
+    for (file_path, parser) in file_paths.iter().zip(&parsers) {
+        for slot in parser.animate_slots() {
+            if slot.is_roblox_default {
+                info!(
+                    "{}: {} -> {} (Roblox default)",
+                    file_path, slot.path, slot.animation_id
+                );
+            } else {
+                info!("{}: {} -> {}", file_path, slot.path, slot.animation_id);
+            }
+        }
+        let ids = parser.animation_ids();
+        file_animation_ids.push(ids.iter().map(u64::to_string).collect());
+        animation_ids.extend(ids);
+    }
+    animation_ids.sort();
+    animation_ids.dedup();
+
+    // Animations from an earlier run (or already replaced by one) don't need uploading again
+    let done: HashSet<String> = animation_mapping
+        .keys()
+        .chain(animation_mapping.values())
+        .cloned()
+        .collect();
+    animation_ids.retain(|asset_id| !done.contains(&asset_id.to_string()));
+
+    let candidates = match first_parser
+        .reupload_candidates(animation_ids, AssetType::Animation)
         .await
     {
-        Ok(animation_mapping) => {
-            // TODO: Instead of scanning and looping through a HashMap of u64, Make a HashMap of
-            // Animations, that includes instances, that way one loop will handle it all.
-            // Also optimize and delete values after updating them.
+        Ok(candidates) => candidates,
+        Err(e) => {
+            error!("Failed to filter animations: {:?}", e);
+            Vec::new()
+        }
+    };
 
-            parser.update_script_animations(&animation_mapping);
-            parser.update_game_animations(&animation_mapping);
+    // Shares the parser's rate limiters with the uploads
+    let uploader = match first_parser.animation_uploader() {
+        Ok(mut uploader) => {
+            if name_template
+                .as_deref()
+                .is_some_and(|t| t.contains("{name}"))
+            {
+                for parser in &parsers[1..] {
+                    for (asset_id, name) in parser.animation_names() {
+                        uploader.animation_names.entry(asset_id).or_insert(name);
+                    }
+                }
+            }
+            Arc::new(uploader)
+        }
+        Err(e) => {
+            error!("Failed to create uploader: {}", e);
+            return ExitCode::FAILURE;
         }
+    };
+
+    // Finished uploads are merged and saved as they arrive, so a crash loses little
+    let (uploaded_sender, mut uploaded_receiver) = mpsc::channel(PIPELINE_CAPACITY);
+    let saver = {
+        let mapping_file = mapping_file.clone();
+        let sources: Vec<(String, HashSet<String>)> = file_paths
+            .iter()
+            .cloned()
+            .zip(file_animation_ids.iter().cloned())
+            .collect();
+        let mut mapping_document = mapping_document;
+        tokio::spawn(async move {
+            let mut last_save = Instant::now();
+            while let Some((old_id, entry)) = uploaded_receiver.recv().await {
+                if let Some(conflict) = mapping_document.insert(old_id.clone(), entry) {
+                    warn!(
+                        old_id = conflict.old_id.as_str(), kept = conflict.kept.as_str(), discarded = conflict.discarded.as_str();
+                        "Animation was already reuploaded, keeping the latest upload"
+                    );
+                }
+                if let Some(entry) = mapping_document.entries.get_mut(&old_id) {
+                    for (file_path, ids) in &sources {
+                        if ids.contains(&old_id) {
+                            entry.add_source_file(file_path.as_str());
+                        }
+                    }
+                }
+
+                let Some(mapping_file) = &mapping_file else {
+                    continue;
+                };
+                if last_save.elapsed() < MAPPING_SAVE_INTERVAL {
+                    continue;
+                }
+                if let Err(e) = mapping_document.save(shellexpand::tilde(mapping_file).to_string())
+                {
+                    error!("{}", e);
+                }
+                last_save = Instant::now();
+            }
+            mapping_document
+        })
+    };
+
+    match uploader
+        .clone()
+        .reupload_pipeline(
+            candidates,
+            AssetType::Animation,
+            group,
+            threads,
+            uploaded_sender,
+        )
+        .await
+    {
+        Ok(summary) => info!(
+            "Reuploaded {} of {} animations ({} failed, {} not started)",
+            summary.uploaded, summary.queued, summary.failed, summary.abandoned
+        ),
+        Err(e) => error!("Failed to upload animations: {:?}", e),
+    }
+    let mut mapping_document = match saver.await {
+        Ok(mapping_document) => mapping_document,
         Err(e) => {
-            eprintln!("Failed to upload animations: {:?}", e);
+            error!("Failed to record uploads: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Record which places reference each mapped animation, for auditing
+    for (file_path, ids) in file_paths.iter().zip(&file_animation_ids) {
+        for (old_id, entry) in mapping_document.entries.iter_mut() {
+            if ids.contains(old_id) {
+                entry.add_source_file(file_path.as_str());
+            }
+        }
+    }
+    animation_mapping = mapping_document.animation_mapping();
+
+    // TODO: Instead of scanning and looping through a HashMap of u64, Make a HashMap of
+    // Animations, that includes instances, that way one loop will handle it all.
+    // Also optimize and delete values after updating them.
+    let mut failed = false;
+    let mut applied = Vec::with_capacity(parsers.len());
+    for (file_path, parser) in file_paths.iter().zip(parsers.iter_mut()) {
+        let scripts = match parser.update_script_animations(&animation_mapping) {
+            Ok(scripts) => scripts,
+            Err(e) => {
+                // Saving would leave the scripts on the old IDs, so the place is skipped
+                error!("{}: {}", file_path, e);
+                failed = true;
+                applied.push(false);
+                continue;
+            }
+        };
+        applied.push(true);
+        let instances = parser.update_game_animations(&animation_mapping);
+        info!(
+            "{}: updated {} scripts and {} instance properties",
+            file_path, scripts, instances
+        );
+        if !parser.skipped_values.is_empty() {
+            warn!(
+                "{}: {} values kept their old ID because they can't hold the new one",
+                file_path,
+                parser.skipped_values.len()
+            );
+        }
+    }
+
+    if let Some(mapping_file) = &mapping_file {
+        let saved = mapping_document.save(shellexpand::tilde(mapping_file).to_string());
+        if let Err(e) = saved {
+            error!("{}", e);
+        }
+    }
+
+    if let Some(place_cache_file) = &place_cache_file {
+        let saved = parsers[0]
+            .place_resolver
+            .place_cache()
+            .save(shellexpand::tilde(place_cache_file).to_string());
+        if let Err(e) = saved {
+            error!("{}", e);
+        }
+    }
+
+    if cancellation.is_cancelled() {
+        warn!(
+            "Run was interrupted; saving {} replaced animations. Run again with the same --mapping-file to resume.",
+            animation_mapping.len()
+        );
+    }
+
+    // Dropping every sender lets the progress bar finish
+    drop(uploader);
+    for parser in parsers.iter_mut() {
+        parser.progress = Progress::default();
+    }
+    let _ = renderer.await;
+
+    for ((file_path, parser), applied) in file_paths.iter().zip(&parsers).zip(applied) {
+        if !applied {
+            continue;
+        }
+        let target = match output_path(file_path, output.as_deref(), file_paths.len() > 1) {
+            Ok(target) => target,
+            Err(e) => {
+                error!("{}", e);
+                failed = true;
+                continue;
+            }
+        };
+        if let Err(e) = parser.save_verified(&target, Some(&animation_mapping)) {
+            error!("Failed to save {}: {}", target, e);
+            failed = true;
         }
     }
 
-    if let Some(output) = args.output {
-        parser.save_to_rbxl(output).unwrap();
+    if failed {
+        ExitCode::FAILURE
     } else {
-        parser.save_to_rbxl(file_path).unwrap();
+        ExitCode::SUCCESS
     }
 }
@@ -0,0 +1,292 @@
+use crate::studio::workplace_editor::write_atomic;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Version written to new mapping documents. Older versions are upgraded when read.
+pub const MAPPING_VERSION: u32 = 1;
+
+/// What is known about one reuploaded animation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MappingEntry {
+    pub new_id: String,
+    /// Creator of the original animation, e.g. `user:1` or `group:2`.
+    #[serde(default)]
+    pub creator: Option<String>,
+    /// Name the animation was reuploaded under.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Group the animation was uploaded to, `None` for the cookie's user.
+    #[serde(default)]
+    pub group_id: Option<u64>,
+    /// Unix time of the upload, in seconds.
+    #[serde(default)]
+    pub uploaded_at: Option<u64>,
+    /// SHA-256 of the downloaded animation file, as lowercase hex.
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Places referencing the original animation when it was reuploaded.
+    #[serde(default)]
+    pub source_files: Vec<String>,
+}
+
+impl MappingEntry {
+    pub fn new<S: Into<String>>(new_id: S) -> Self {
+        Self {
+            new_id: new_id.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Fills in whatever this entry is missing from another entry for the same upload.
+    fn absorb(&mut self, other: MappingEntry) {
+        self.creator = self.creator.take().or(other.creator);
+        self.name = self.name.take().or(other.name);
+        self.group_id = self.group_id.or(other.group_id);
+        self.uploaded_at = self.uploaded_at.or(other.uploaded_at);
+        self.sha256 = self.sha256.take().or(other.sha256);
+        for source_file in other.source_files {
+            self.add_source_file(source_file);
+        }
+    }
+
+    pub fn add_source_file<S: Into<String>>(&mut self, source_file: S) {
+        let source_file = source_file.into();
+        if !self.source_files.contains(&source_file) {
+            self.source_files.push(source_file);
+            self.source_files.sort();
+        }
+    }
+}
+
+/// Two mappings reuploaded the same original animation as different assets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappingConflict {
+    pub old_id: String,
+    /// The new ID the merged document keeps, the most recent upload.
+    pub kept: String,
+    pub discarded: String,
+}
+
+/// A versioned old -> new animation ID mapping with metadata for auditing, keyed by the
+/// original asset ID.
+///
+/// * Notes
+/// Bare `{"old": "new"}` maps written by older versions are read as entries without
+/// metadata. Entries are saved sorted by original ID so documents diff and merge cleanly
+/// in version control.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut document = MappingDocument::load("mapping.json")?;
+/// let conflicts = document.merge(MappingDocument::load("teammate.json")?);
+/// parser.update_game_animations(&document.animation_mapping());
+/// document.save("mapping.json")?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MappingDocument {
+    pub version: u32,
+    pub entries: IndexMap<String, MappingEntry>,
+}
+
+impl Default for MappingDocument {
+    fn default() -> Self {
+        Self {
+            version: MAPPING_VERSION,
+            entries: IndexMap::new(),
+        }
+    }
+}
+
+impl MappingDocument {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wraps a bare old -> new map, without metadata.
+    pub fn from_animation_mapping(animation_mapping: &HashMap<String, String>) -> Self {
+        let mut document = Self::new();
+        for (old_id, new_id) in animation_mapping {
+            document
+                .entries
+                .insert(old_id.clone(), MappingEntry::new(new_id.clone()));
+        }
+        document
+    }
+
+    /// The old -> new map the update functions take.
+    pub fn animation_mapping(&self) -> HashMap<String, String> {
+        self.entries
+            .iter()
+            .map(|(old_id, entry)| (old_id.clone(), entry.new_id.clone()))
+            .collect()
+    }
+
+    /// Adds one entry, merging it like `merge` does when the original ID is already mapped.
+    pub fn insert(&mut self, old_id: String, entry: MappingEntry) -> Option<MappingConflict> {
+        let Some(existing) = self.entries.get_mut(&old_id) else {
+            self.entries.insert(old_id, entry);
+            return None;
+        };
+
+        if existing.new_id == entry.new_id {
+            existing.absorb(entry);
+            return None;
+        }
+
+        // Different uploads of the same animation: keep the latest, unknown times count as oldest
+        let (kept, discarded) = if entry.uploaded_at > existing.uploaded_at {
+            let discarded = std::mem::replace(existing, entry);
+            (existing.new_id.clone(), discarded.new_id)
+        } else {
+            (existing.new_id.clone(), entry.new_id)
+        };
+        Some(MappingConflict {
+            old_id,
+            kept,
+            discarded,
+        })
+    }
+
+    /// Merges another document (another run, or a teammate's mapping) into this one.
+    /// Entries for the same upload are combined; when the same original animation was
+    /// uploaded twice, the most recent upload wins and the other is returned as a conflict.
+    pub fn merge(&mut self, other: MappingDocument) -> Vec<MappingConflict> {
+        other
+            .entries
+            .into_iter()
+            .filter_map(|(old_id, entry)| self.insert(old_id, entry))
+            .collect()
+    }
+
+    /// Loads a mapping document, or a bare map from an older version.
+    /// A missing file is an empty document, so the first run of a resumable job just works.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+
+        let contents = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read mapping '{}': {}", path.display(), e))?;
+        Self::from_json(&contents)
+            .map_err(|e| anyhow::anyhow!("Failed to parse mapping '{}': {}", path.display(), e))
+    }
+
+    /// Parses a mapping document, or a bare map from an older version.
+    /// Fails when an old or new ID isn't an asset ID, so a damaged entry can't rewrite
+    /// references to an empty ID.
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        if value.get("version").is_none() {
+            let animation_mapping: HashMap<String, String> = serde_json::from_value(value)?;
+            let document = Self::from_animation_mapping(&animation_mapping);
+            document.check_ids()?;
+            return Ok(document);
+        }
+
+        let mut document: MappingDocument = serde_json::from_value(value)?;
+        if document.version > MAPPING_VERSION {
+            return Err(anyhow::anyhow!(
+                "Mapping version {} is newer than this version supports ({})",
+                document.version,
+                MAPPING_VERSION
+            ));
+        }
+        document.version = MAPPING_VERSION;
+        document.check_ids()?;
+        Ok(document)
+    }
+
+    fn check_ids(&self) -> anyhow::Result<()> {
+        let is_asset_id = |id: &str| id.parse::<u64>().is_ok();
+        for (old_id, entry) in &self.entries {
+            if !is_asset_id(old_id) {
+                return Err(anyhow::anyhow!("'{}' isn't an animation ID", old_id));
+            }
+            if !is_asset_id(&entry.new_id) {
+                return Err(anyhow::anyhow!(
+                    "Animation {} is mapped to '{}', which isn't an animation ID",
+                    old_id,
+                    entry.new_id
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the document as pretty printed JSON, sorted by original ID.
+    ///
+    /// * Notes
+    /// The file is replaced atomically, so a run interrupted while saving still resumes from
+    /// the previous save.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let mut document = self.clone();
+        document
+            .entries
+            .sort_by(|old_a, _, old_b, _| sort_key(old_a).cmp(&sort_key(old_b)));
+        let json = serde_json::to_string_pretty(&document)?;
+        write_atomic(path, json.as_bytes())
+            .map_err(|e| anyhow::anyhow!("Failed to save mapping '{}': {}", path.display(), e))
+    }
+}
+
+/// Orders IDs by value, so `9` comes before `10`. IDs that aren't numbers sort first.
+fn sort_key(id: &str) -> (Option<u64>, &str) {
+    (id.parse().ok(), id)
+}
+
+/// Loads the old -> new animation ID map of a mapping saved by `save_mapping` or
+/// `MappingDocument::save`, dropping the metadata.
+/// A missing file is an empty mapping, so the first run of a resumable job just works.
+///
+/// # Examples
+///
+/// ```rust
+/// let mapping = load_mapping("mapping.json")?;
+/// parser.update_script_animations(&mapping)?;
+/// ```
+pub fn load_mapping<P: AsRef<Path>>(path: P) -> anyhow::Result<HashMap<String, String>> {
+    Ok(MappingDocument::load(path)?.animation_mapping())
+}
+
+/// Writes an old -> new animation ID mapping as a mapping document without metadata.
+pub fn save_mapping<P: AsRef<Path>>(
+    path: P,
+    mapping: &HashMap<String, String>,
+) -> anyhow::Result<()> {
+    MappingDocument::from_animation_mapping(mapping).save(path)
+}
+
+/// Flips an old -> new mapping into new -> old, to restore the original IDs.
+///
+/// * Notes
+/// Fails when two old IDs were mapped to the same new ID, since there's no way to know which
+/// one a reference should go back to.
+///
+/// # Examples
+///
+/// ```rust
+/// let mapping = load_mapping("mapping.json")?;
+/// parser.update_game_animations(&invert_mapping(&mapping)?);
+/// ```
+pub fn invert_mapping(
+    mapping: &HashMap<String, String>,
+) -> anyhow::Result<HashMap<String, String>> {
+    let mut inverted = HashMap::with_capacity(mapping.len());
+    for (old_id, new_id) in mapping {
+        if let Some(other_old_id) = inverted.insert(new_id.clone(), old_id.clone()) {
+            return Err(anyhow::anyhow!(
+                "Mapping can't be inverted: {} and {} were both reuploaded as {}",
+                other_old_id,
+                old_id,
+                new_id
+            ));
+        }
+    }
+    Ok(inverted)
+}
@@ -0,0 +1,86 @@
+use crate::Creator;
+use crate::studio::workplace_editor::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// How many of a creator's games are tried before an asset is given up on.
+pub const MAX_PLACE_CANDIDATES: usize = 10;
+
+/// Remembers which place ID lets the batch metadata API serve each creator's assets.
+///
+/// * Notes
+/// Only `places` is saved: places that worked in an earlier batch or run. `overrides` come
+/// from the config and are tried first.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut cache = PlaceCache::load("place-cache.json")?;
+/// cache.overrides.insert(Creator::Group(123), 456);
+///
+/// let parser = StudioParser::builder()
+///     .file_path("MyPlace.rbxl")
+///     .place_cache(cache)
+///     .build()?;
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaceCache {
+    pub places: HashMap<Creator, u64>,
+    #[serde(skip)]
+    pub overrides: HashMap<Creator, u64>,
+}
+
+impl PlaceCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the places to use for creators whose games can't be discovered.
+    pub fn with_overrides(mut self, overrides: HashMap<Creator, u64>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// The override and the cached place of a creator, in the order they should be tried.
+    pub fn known_places(&self, creator: Creator) -> Vec<u64> {
+        let mut places: Vec<u64> = self.overrides.get(&creator).copied().into_iter().collect();
+        let cached = self
+            .places
+            .get(&creator)
+            .copied()
+            .filter(|place_id| !places.contains(place_id));
+        places.extend(cached);
+        places
+    }
+
+    /// Records a place that resolved one of the creator's assets.
+    pub fn remember(&mut self, creator: Creator, place_id: u64) {
+        self.places.insert(creator, place_id);
+    }
+
+    /// Loads a cache saved by `save`. A missing file is an empty cache.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+
+        let contents = fs::read_to_string(path).map_err(|e| {
+            anyhow::anyhow!("Failed to read place cache '{}': {}", path.display(), e)
+        })?;
+        serde_json::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Failed to parse place cache '{}': {}", path.display(), e))
+    }
+
+    /// Writes the places that worked as JSON, keyed by `user:<id>` or `group:<id>`. The file
+    /// is replaced atomically, so an interrupted save keeps the previous cache.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(self)?;
+        write_atomic(path, json.as_bytes())
+            .map_err(|e| anyhow::anyhow!("Failed to save place cache '{}': {}", path.display(), e))
+    }
+}
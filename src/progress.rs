@@ -0,0 +1,98 @@
+use crate::asset::AssetType;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Where scanned asset IDs were found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanSource {
+    /// Instance properties, value objects and attributes.
+    Workspace,
+    /// `rbxassetid://` strings in script sources.
+    Scripts,
+}
+
+/// What an apply step rewrote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApplyTarget {
+    Scripts,
+    Instances,
+}
+
+/// Typed progress of a scan, reupload and apply run.
+#[derive(Debug, Clone)]
+pub enum ProgressEvent {
+    /// A scan found `count` IDs that pass the filters and will be fetched.
+    ScanFound {
+        source: ScanSource,
+        asset_type: AssetType,
+        count: usize,
+    },
+    /// One batch of the metadata API came back.
+    BatchFetched {
+        requested: usize,
+        fetched: usize,
+    },
+    /// `count` more animations have a download location and will be reuploaded. Sent once
+    /// per run, or once per metadata batch when streaming.
+    UploadsQueued {
+        count: usize,
+    },
+    DownloadStarted {
+        asset_id: String,
+    },
+    DownloadFinished {
+        asset_id: String,
+    },
+    UploadSucceeded {
+        asset_id: String,
+        new_asset_id: String,
+    },
+    /// The download or the upload failed after every retry.
+    UploadFailed {
+        asset_id: String,
+        error: String,
+    },
+    /// New IDs were written into `updated` scripts or instance properties.
+    ApplyFinished {
+        target: ApplyTarget,
+        updated: usize,
+    },
+}
+
+pub type ProgressSender = UnboundedSender<ProgressEvent>;
+
+/// Sends progress events to a listener, if there is one.
+///
+/// # Examples
+///
+/// ```rust
+/// let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+/// let parser = StudioParser::builder()
+///     .file_path("MyPlace.rbxl")
+///     .progress(sender)
+///     .build()?;
+///
+/// tokio::spawn(async move {
+///     while let Some(event) = receiver.recv().await {
+///         println!("{:?}", event);
+///     }
+/// });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    sender: Option<ProgressSender>,
+}
+
+impl Progress {
+    pub fn new(sender: ProgressSender) -> Self {
+        Self {
+            sender: Some(sender),
+        }
+    }
+
+    /// Sends an event. Nobody listening, or a listener that went away, is not an error.
+    pub fn emit(&self, event: ProgressEvent) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(event);
+        }
+    }
+}
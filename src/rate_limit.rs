@@ -0,0 +1,269 @@
+use crate::retry::{ErrorClass, RetryPolicy};
+use log::warn;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{Instant, sleep, timeout};
+
+/// Groups of Roblox endpoints that are rate limited separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiFamily {
+    /// Animation uploads.
+    Upload,
+    /// Asset info lookups (creator of an asset).
+    AssetInfo,
+    /// The asset delivery batch metadata API.
+    BatchMetadata,
+    /// User and group game listings, used to find a place ID.
+    Games,
+    /// Downloading asset files from the CDN locations returned by the batch API.
+    Download,
+    /// The authenticated user and their group permissions, checked before a run.
+    Account,
+}
+
+impl ApiFamily {
+    /// Whether a request of the family can be sent again after it may have reached Roblox.
+    /// Uploads create a new asset every time one gets through, so they can't.
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(self, ApiFamily::Upload)
+    }
+}
+
+/// Returned by requests this crate sends itself when Roblox answers 429,
+/// so the `Retry-After` header isn't lost on the way to the retry loop.
+#[derive(Debug)]
+pub struct RateLimited {
+    pub retry_after: Option<Duration>,
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.retry_after {
+            Some(retry_after) => write!(f, "Rate limited, retry after {:?}", retry_after),
+            None => write!(f, "Rate limited"),
+        }
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Reads a `Retry-After` header given in seconds.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// Turns a 429 response into a `RateLimited` error, passing other responses through.
+pub fn check_rate_limited(response: reqwest::Response) -> anyhow::Result<reqwest::Response> {
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        return Err(RateLimited {
+            retry_after: retry_after(response.headers()),
+        }
+        .into());
+    }
+    Ok(response)
+}
+
+/// A token bucket shared by every request of one API family.
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_second: f64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+    paused_until: Option<Instant>,
+}
+
+impl RateLimiter {
+    /// `capacity` requests may burst at once, then `refill_per_second` requests per second.
+    pub fn new(capacity: u32, refill_per_second: f64) -> Self {
+        Self {
+            capacity: capacity.max(1) as f64,
+            refill_per_second: refill_per_second.max(0.01),
+            state: Mutex::new(BucketState {
+                tokens: capacity.max(1) as f64,
+                last_refill: Instant::now(),
+                paused_until: None,
+            }),
+        }
+    }
+
+    /// Waits until the family may send another request.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().await;
+                let now = Instant::now();
+
+                match state.paused_until {
+                    Some(paused_until) if paused_until > now => paused_until - now,
+                    _ => {
+                        state.paused_until = None;
+
+                        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+                        state.tokens =
+                            (state.tokens + elapsed * self.refill_per_second).min(self.capacity);
+                        state.last_refill = now;
+
+                        if state.tokens >= 1.0 {
+                            state.tokens -= 1.0;
+                            return;
+                        }
+
+                        Duration::from_secs_f64((1.0 - state.tokens) / self.refill_per_second)
+                    }
+                }
+            };
+
+            sleep(wait).await;
+        }
+    }
+
+    /// Stops every request of the family for `duration`, e.g. after a 429.
+    pub async fn pause_for(&self, duration: Duration) {
+        let mut state = self.state.lock().await;
+        let until = Instant::now() + duration;
+        if state
+            .paused_until
+            .is_none_or(|paused_until| paused_until < until)
+        {
+            state.paused_until = Some(until);
+        }
+    }
+}
+
+/// One limiter per API family.
+/// Shared (behind an `Arc`) by every uploader created from the same parser.
+#[derive(Debug)]
+pub struct RateLimits {
+    pub upload: RateLimiter,
+    pub asset_info: RateLimiter,
+    pub batch_metadata: RateLimiter,
+    pub games: RateLimiter,
+    pub download: RateLimiter,
+    pub account: RateLimiter,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            upload: RateLimiter::new(5, 1.0),
+            asset_info: RateLimiter::new(10, 5.0),
+            batch_metadata: RateLimiter::new(2, 1.0),
+            games: RateLimiter::new(10, 5.0),
+            download: RateLimiter::new(20, 10.0),
+            account: RateLimiter::new(5, 2.0),
+        }
+    }
+}
+
+impl RateLimits {
+    pub fn limiter(&self, family: ApiFamily) -> &RateLimiter {
+        match family {
+            ApiFamily::Upload => &self.upload,
+            ApiFamily::AssetInfo => &self.asset_info,
+            ApiFamily::BatchMetadata => &self.batch_metadata,
+            ApiFamily::Games => &self.games,
+            ApiFamily::Download => &self.download,
+            ApiFamily::Account => &self.account,
+        }
+    }
+
+    /// Sends a request through the family's limiter, retrying the error classes the policy
+    /// allows until it runs out of attempts. Each attempt of an idempotent family is cut off
+    /// after the policy's (growing) timeout; other families run until the request finishes,
+    /// since a cut off request may still go through.
+    ///
+    /// * Notes
+    /// A 429 pauses the whole family (for `Retry-After` when known) so concurrent tasks
+    /// back off together instead of hammering the API. Families that aren't idempotent are
+    /// only retried on errors that prove the request had no effect (429s and failed
+    /// connections).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let games = limits
+    ///     .call(ApiFamily::Games, &policy, || async { Ok(client.user_games(user_id).await?) })
+    ///     .await?;
+    /// ```
+    pub async fn call<T, F, Fut>(
+        &self,
+        family: ApiFamily,
+        policy: &RetryPolicy,
+        mut request: F,
+    ) -> anyhow::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let limiter = self.limiter(family);
+        let max_attempts = policy.max_attempts.max(1);
+        let mut attempt = 0;
+
+        loop {
+            limiter.acquire().await;
+
+            let result = if family.is_idempotent() {
+                timeout(policy.timeout_for(attempt), request())
+                    .await
+                    .unwrap_or_else(|elapsed| Err(elapsed.into()))
+            } else {
+                request().await
+            };
+            let error = match result {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+
+            if attempt + 1 >= max_attempts {
+                return Err(error);
+            }
+
+            let Some(class) = policy.retryable_class(&error) else {
+                return Err(error);
+            };
+            if !family.is_idempotent() && !class.had_no_effect() {
+                return Err(error);
+            }
+
+            if class == ErrorClass::RateLimited {
+                let delay = error
+                    .downcast_ref::<RateLimited>()
+                    .and_then(|rate_limited| rate_limited.retry_after)
+                    .unwrap_or_else(|| policy.delay(attempt));
+                warn!(
+                    family:? = family, attempt = attempt + 1, max_attempts = max_attempts;
+                    "{:?} API rate limited, waiting {:?}",
+                    family,
+                    delay
+                );
+                limiter.pause_for(delay).await;
+            } else {
+                let delay = policy.delay(attempt);
+                warn!(
+                    family:? = family, error_class = class.as_str(), attempt = attempt + 1, max_attempts = max_attempts;
+                    "{:?} request failed, retrying in {:?}: {}",
+                    family,
+                    delay,
+                    error
+                );
+                sleep(delay).await;
+            }
+
+            attempt += 1;
+        }
+    }
+}
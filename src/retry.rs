@@ -0,0 +1,199 @@
+use crate::rate_limit::RateLimited;
+use rand::Rng;
+use roboat::RoboatError;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Kinds of failures a request can be retried on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ErrorClass {
+    /// 429 Too Many Requests.
+    RateLimited,
+    /// The request didn't finish within the attempt's timeout.
+    Timeout,
+    /// Connection failures.
+    Network,
+    /// 5xx responses.
+    ServerError,
+    /// Roblox answered with a body that couldn't be parsed.
+    MalformedResponse,
+}
+
+impl ErrorClass {
+    pub const ALL: [ErrorClass; 5] = [
+        ErrorClass::RateLimited,
+        ErrorClass::Timeout,
+        ErrorClass::Network,
+        ErrorClass::ServerError,
+        ErrorClass::MalformedResponse,
+    ];
+
+    /// Works out which class an error belongs to. Errors outside every class
+    /// (bad requests, invalid cookies...) are never retried.
+    pub fn of(error: &anyhow::Error) -> Option<Self> {
+        if error.is::<RateLimited>() {
+            return Some(ErrorClass::RateLimited);
+        }
+
+        if let Some(roboat_error) = error.downcast_ref::<RoboatError>() {
+            return match roboat_error {
+                RoboatError::TooManyRequests => Some(ErrorClass::RateLimited),
+                RoboatError::InternalServerError => Some(ErrorClass::ServerError),
+                RoboatError::MalformedResponse => Some(ErrorClass::MalformedResponse),
+                RoboatError::ReqwestError(e) => Self::of_reqwest(e),
+                _ => None,
+            };
+        }
+
+        if let Some(e) = error.downcast_ref::<reqwest::Error>() {
+            return Self::of_reqwest(e);
+        }
+
+        if error.is::<tokio::time::error::Elapsed>() {
+            return Some(ErrorClass::Timeout);
+        }
+
+        None
+    }
+
+    fn of_reqwest(error: &reqwest::Error) -> Option<Self> {
+        if error.is_timeout() {
+            Some(ErrorClass::Timeout)
+        } else if error.is_connect() {
+            Some(ErrorClass::Network)
+        } else if error
+            .status()
+            .is_some_and(|status| status.is_server_error())
+        {
+            Some(ErrorClass::ServerError)
+        } else {
+            None
+        }
+    }
+
+    /// Whether a request that failed this way can't have changed anything on Roblox: it was
+    /// turned away with a 429 or never got a connection.
+    pub fn had_no_effect(&self) -> bool {
+        matches!(self, ErrorClass::RateLimited | ErrorClass::Network)
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ErrorClass::RateLimited => "rate-limited",
+            ErrorClass::Timeout => "timeout",
+            ErrorClass::Network => "network",
+            ErrorClass::ServerError => "server-error",
+            ErrorClass::MalformedResponse => "malformed-response",
+        }
+    }
+}
+
+impl TryFrom<String> for ErrorClass {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl FromStr for ErrorClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ErrorClass::ALL
+            .into_iter()
+            .find(|class| class.as_str() == s.trim().to_lowercase())
+            .ok_or_else(|| {
+                format!(
+                    "Unknown error class '{}' (expected rate-limited, timeout, network, server-error or malformed-response)",
+                    s
+                )
+            })
+    }
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How often and how patiently requests are retried.
+/// Used by the metadata fetch, the file downloads and the uploads alike.
+///
+/// * Notes
+/// Uploads aren't idempotent: they get no attempt timeout and are only retried on 429s and
+/// failed connections, whatever `retryable` says (see `ApiFamily::is_idempotent`).
+///
+/// # Examples
+///
+/// ```rust
+/// let policy = RetryPolicy {
+///     max_attempts: 10,
+///     timeout: Duration::from_secs(20),
+///     ..Default::default()
+/// };
+///
+/// let parser = StudioParser::builder()
+///     .file_path("MyPlace.rbxl")
+///     .retry_policy(policy)
+///     .build()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts per request, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on every retry after that.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Timeout of the first attempt.
+    pub timeout: Duration,
+    /// Added to the timeout on every retry, so slow endpoints get more time.
+    pub timeout_growth: Duration,
+    pub retryable: HashSet<ErrorClass>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 6,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            timeout: Duration::from_secs(10),
+            timeout_growth: Duration::from_secs(5),
+            retryable: ErrorClass::ALL.into_iter().collect(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `retry` (starting at 0): `base * 2^retry`, capped at
+    /// `max_delay`, then jittered down to between 50% and 100% of that.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry.min(16)))
+            .min(self.max_delay);
+        exponential.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+
+    /// Timeout for attempt number `attempt` (starting at 0).
+    pub fn timeout_for(&self, attempt: u32) -> Duration {
+        self.timeout
+            .saturating_add(self.timeout_growth.saturating_mul(attempt))
+    }
+
+    /// Timeout of the last attempt, the longest any single request may take.
+    pub fn longest_timeout(&self) -> Duration {
+        self.timeout_for(self.max_attempts.max(1) - 1)
+    }
+
+    /// Returns the class of a retryable error, or None when it should fail right away.
+    pub fn retryable_class(&self, error: &anyhow::Error) -> Option<ErrorClass> {
+        ErrorClass::of(error).filter(|class| self.retryable.contains(class))
+    }
+}
@@ -0,0 +1,146 @@
+use crate::StudioParser;
+use crate::filter::AnimationFilter;
+use crate::mapping::invert_mapping;
+use crate::script::script_editor::IdReplacer;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+/// Text files of a Rojo project that can reference animation IDs.
+const ROJO_EXTENSIONS: [&str; 3] = ["lua", "luau", "json"];
+
+/// What a revert put back.
+#[derive(Debug, Clone, Default)]
+pub struct RevertReport {
+    /// Reuploaded ID -> original ID, for every reuploaded ID that was replaced.
+    pub restored: BTreeMap<u64, u64>,
+    /// Properties, values and attributes rewritten in a place or model.
+    pub instances: usize,
+    /// Scripts rewritten in a place or model, or source files rewritten in a Rojo tree.
+    pub scripts: usize,
+    /// Files changed in a Rojo tree.
+    pub files: Vec<PathBuf>,
+}
+
+impl StudioParser {
+    /// Restores the original animation IDs of a place or model from a saved old -> new
+    /// mapping, through `update_game_animations` and `update_script_animations`.
+    ///
+    /// * Notes
+    /// The parser's filter applies to the original IDs, as it did when the mapping was made.
+    /// Nothing is saved, call `save_verified` with the inverted mapping afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut parser = StudioParser::builder().file_path("output.rbxl").build()?;
+    /// let report = parser.revert_animations(&load_mapping("mapping.json")?)?;
+    /// println!("Restored {} IDs", report.restored.len());
+    /// parser.save_verified("output.rbxl", Some(&invert_mapping(&mapping)?))?;
+    /// ```
+    pub fn revert_animations(
+        &mut self,
+        animation_mapping: &HashMap<String, String>,
+    ) -> anyhow::Result<RevertReport> {
+        let inverted = allowed_inverse(animation_mapping, &self.filter)?;
+
+        let mut report = RevertReport::default();
+
+        // The update functions check ID rules against the IDs they replace, here the
+        // reuploaded ones, so the rules already applied above are lifted meanwhile.
+        // They record every ID they replace, which is what this revert restored.
+        let allowed_ids = mem::take(&mut self.filter.allowed_ids);
+        let denied_ids = mem::take(&mut self.filter.denied_ids);
+        let earlier_ids = mem::take(&mut self.rewritten_ids);
+        report.instances = self.update_game_animations(&inverted);
+        let scripts = self.update_script_animations(&inverted);
+        self.filter.allowed_ids = allowed_ids;
+        self.filter.denied_ids = denied_ids;
+
+        for &asset_id in &self.rewritten_ids {
+            let original_id = inverted
+                .get(&asset_id.to_string())
+                .and_then(|original_id| original_id.parse::<u64>().ok());
+            if let Some(original_id) = original_id {
+                report.restored.insert(asset_id, original_id);
+            }
+        }
+        self.rewritten_ids.extend(earlier_ids);
+        report.scripts = scripts?;
+
+        Ok(report)
+    }
+}
+
+/// Restores the original animation IDs in the `.lua`, `.luau` and `.json` files of a Rojo
+/// project, rewriting the files in place.
+///
+/// * Notes
+/// Only ID rules of the filter apply, path and script rules need a DOM.
+///
+/// # Examples
+///
+/// ```rust
+/// let report = revert_rojo_tree("src", &load_mapping("mapping.json")?, &AnimationFilter::new())?;
+/// for file in &report.files {
+///     println!("Restored {}", file.display());
+/// }
+/// ```
+pub fn revert_rojo_tree<P: AsRef<Path>>(
+    root: P,
+    animation_mapping: &HashMap<String, String>,
+    filter: &AnimationFilter,
+) -> anyhow::Result<RevertReport> {
+    let root = root.as_ref();
+    let inverted = allowed_inverse(animation_mapping, filter)?;
+    let replacer = IdReplacer::new(&inverted, &AnimationFilter::new())?;
+
+    let mut report = RevertReport::default();
+    for extension in ROJO_EXTENSIONS {
+        let pattern = format!(
+            "{}/**/*.{}",
+            glob::Pattern::escape(&root.to_string_lossy()),
+            extension
+        );
+        let entries = glob::glob(&pattern)
+            .map_err(|e| anyhow::anyhow!("Invalid Rojo tree path '{}': {}", root.display(), e))?;
+
+        for file_path in entries {
+            let file_path = file_path?;
+            let source = fs::read_to_string(&file_path)
+                .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", file_path.display(), e))?;
+
+            let Some(rewrite) = replacer.replace(&source) else {
+                continue;
+            };
+            for (new_id, original_id) in &rewrite.replaced {
+                if let (Ok(new_id), Ok(original_id)) =
+                    (new_id.parse::<u64>(), original_id.parse::<u64>())
+                {
+                    report.restored.insert(new_id, original_id);
+                }
+            }
+
+            fs::write(&file_path, &rewrite.source)
+                .map_err(|e| anyhow::anyhow!("Failed to write '{}': {}", file_path.display(), e))?;
+            report.scripts += 1;
+            report.files.push(file_path);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Inverts the mapping, keeping only entries whose original ID the filter allows.
+fn allowed_inverse(
+    animation_mapping: &HashMap<String, String>,
+    filter: &AnimationFilter,
+) -> anyhow::Result<HashMap<String, String>> {
+    let allowed: HashMap<String, String> = animation_mapping
+        .iter()
+        .filter(|(old_id, _)| filter.allows_id_str(old_id))
+        .map(|(old_id, new_id)| (old_id.clone(), new_id.clone()))
+        .collect();
+    invert_mapping(&allowed)
+}
@@ -1,33 +1,261 @@
 use crate::StudioParser;
+use crate::filter::AnimationFilter;
+use crate::progress::{ApplyTarget, ProgressEvent};
+use aho_corasick::{AhoCorasick, MatchKind};
 use rbx_types::Variant;
 use std::collections::HashMap;
 use ustr::Ustr;
 
 impl StudioParser {
     /// Updates animation IDs in script source code using the provided mapping.
-    pub fn update_script_animations(&mut self, animation_mapping: &HashMap<String, String>) {
+    /// Scripts and IDs rejected by the parser's filter are left untouched.
+    /// Returns how many scripts changed, or an error when the mapping is too large to search.
+    ///
+    /// * Notes
+    /// Each source is searched once for every mapped ID at the same time and only rewritten
+    /// when it contains one, so large places with thousands of scripts and mapping entries
+    /// don't copy every source once per entry.
+    pub fn update_script_animations(
+        &mut self,
+        animation_mapping: &HashMap<String, String>,
+    ) -> anyhow::Result<usize> {
+        let mut updated = 0;
+        let replacer = IdReplacer::new(animation_mapping, &self.filter)?;
+
         // Collect script refs first to avoid borrow checker issues
-        let script_refs = self.get_script_refs();
+        let script_refs = if replacer.is_empty() {
+            Vec::new()
+        } else {
+            self.filtered_script_refs()
+        };
 
-        // Now modify each script
         for script_ref in script_refs {
-            if let Some(instance) = self.dom.get_by_ref_mut(script_ref) {
-                if let Some(Variant::String(source)) =
-                    instance.properties.get(&Ustr::from("Source"))
-                {
-                    let mut new_source = source.clone();
-
-                    // Replace animation IDs in the source code
-                    for (old_id, new_id) in animation_mapping {
-                        new_source = new_source.replace(old_id, new_id);
-                    }
-
-                    // Update the source property
-                    instance
-                        .properties
-                        .insert(Ustr::from("Source"), Variant::String(new_source));
-                }
+            let Some(instance) = self.dom.get_by_ref_mut(script_ref) else {
+                continue;
+            };
+            let Some(Variant::String(source)) = instance.properties.get_mut(&Ustr::from("Source"))
+            else {
+                continue;
+            };
+            if let Some(rewrite) = replacer.replace(source) {
+                self.rewritten_ids.extend(
+                    rewrite
+                        .replaced
+                        .iter()
+                        .filter_map(|(old_id, _)| old_id.parse::<u64>().ok()),
+                );
+                *source = rewrite.source;
+                updated += 1;
+            }
+        }
+
+        self.progress.emit(ProgressEvent::ApplyFinished {
+            target: ApplyTarget::Scripts,
+            updated,
+        });
+        Ok(updated)
+    }
+}
+
+/// Replaces many animation IDs in one pass over a source, with an Aho-Corasick automaton
+/// built once per mapping.
+///
+/// * Notes
+/// Only whole numbers are replaced: `123` is not touched inside `41234`. IDs are replaced
+/// once, so a new ID that is also an old ID in the mapping isn't replaced again.
+///
+/// # Examples
+///
+/// ```rust
+/// let replacer = IdReplacer::new(&mapping, &AnimationFilter::new())?;
+/// if let Some(rewrite) = replacer.replace("local id = \"rbxassetid://507766388\"") {
+///     println!("{} IDs replaced:\n{}", rewrite.replaced.len(), rewrite.source);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct IdReplacer {
+    automaton: Option<AhoCorasick>,
+    /// Old IDs, indexed by the automaton's pattern IDs.
+    old_ids: Vec<String>,
+    /// New IDs, indexed by the automaton's pattern IDs.
+    new_ids: Vec<String>,
+}
+
+/// A source rewritten by `IdReplacer::replace`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rewrite<'a> {
+    pub source: String,
+    /// Old and new ID of every replacement made, in source order.
+    pub replaced: Vec<(&'a str, &'a str)>,
+}
+
+impl IdReplacer {
+    /// Builds a replacer for the mapping entries whose old ID the filter allows.
+    /// Fails when the mapping is too large for the automaton.
+    pub fn new(
+        animation_mapping: &HashMap<String, String>,
+        filter: &AnimationFilter,
+    ) -> anyhow::Result<Self> {
+        let (old_ids, new_ids): (Vec<String>, Vec<String>) = animation_mapping
+            .iter()
+            .filter(|(old_id, _)| filter.allows_id_str(old_id))
+            .map(|(old_id, new_id)| (old_id.clone(), new_id.clone()))
+            .unzip();
+
+        let automaton = if old_ids.is_empty() {
+            None
+        } else {
+            let automaton = AhoCorasick::builder()
+                .match_kind(MatchKind::LeftmostLongest)
+                .build(&old_ids)
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to build the ID search for {} mapping entries: {}",
+                        old_ids.len(),
+                        e
+                    )
+                })?;
+            Some(automaton)
+        };
+
+        Ok(Self {
+            automaton,
+            old_ids,
+            new_ids,
+        })
+    }
+
+    /// True when no ID can be replaced, so scripts don't need to be visited at all.
+    pub fn is_empty(&self) -> bool {
+        self.automaton.is_none()
+    }
+
+    /// The source with every mapped ID replaced, or None when it has none.
+    pub fn replace(&self, source: &str) -> Option<Rewrite<'_>> {
+        let automaton = self.automaton.as_ref()?;
+        let bytes = source.as_bytes();
+        let is_digit = |index: usize| bytes.get(index).is_some_and(u8::is_ascii_digit);
+
+        let mut new_source: Option<String> = None;
+        let mut replaced = Vec::new();
+        let mut copied_up_to = 0;
+        for found in automaton.find_iter(source) {
+            let whole_number =
+                !(found.start() > 0 && is_digit(found.start() - 1)) && !is_digit(found.end());
+            if !whole_number {
+                continue;
             }
+
+            let new_source = new_source.get_or_insert_with(|| String::with_capacity(source.len()));
+            let pattern = found.pattern().as_usize();
+            new_source.push_str(&source[copied_up_to..found.start()]);
+            new_source.push_str(&self.new_ids[pattern]);
+            replaced.push((
+                self.old_ids[pattern].as_str(),
+                self.new_ids[pattern].as_str(),
+            ));
+            copied_up_to = found.end();
         }
+
+        let mut new_source = new_source?;
+        new_source.push_str(&source[copied_up_to..]);
+        Some(Rewrite {
+            source: new_source,
+            replaced,
+        })
+    }
+}
+
+/// Replaces every mapped ID the filter allows in a script source. Shared by the place editor
+/// and the Rojo tree revert, which rewrites `.lua`/`.luau` files directly.
+///
+/// * Notes
+/// Builds an `IdReplacer` on every call, keep one around when rewriting many sources.
+pub fn replace_ids_in_source(
+    source: &str,
+    animation_mapping: &HashMap<String, String>,
+    filter: &AnimationFilter,
+) -> anyhow::Result<String> {
+    Ok(IdReplacer::new(animation_mapping, filter)?
+        .replace(source)
+        .map_or_else(|| source.to_string(), |rewrite| rewrite.source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(old_id, new_id)| (old_id.to_string(), new_id.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn skips_ids_inside_longer_numbers() {
+        let replacer =
+            IdReplacer::new(&mapping(&[("123", "900")]), &AnimationFilter::new()).unwrap();
+
+        assert_eq!(replacer.replace("local id = 1234"), None);
+        assert_eq!(replacer.replace("local id = 41234"), None);
+
+        let rewrite = replacer.replace("local a, b = 123, 1234").unwrap();
+        assert_eq!(rewrite.source, "local a, b = 900, 1234");
+        assert_eq!(rewrite.replaced, vec![("123", "900")]);
+    }
+
+    #[test]
+    fn prefers_the_longest_id() {
+        let replacer = IdReplacer::new(
+            &mapping(&[("123", "900"), ("1234", "9000")]),
+            &AnimationFilter::new(),
+        )
+        .unwrap();
+
+        let rewrite = replacer.replace("local a, b = 1234, 123").unwrap();
+        assert_eq!(rewrite.source, "local a, b = 9000, 900");
+        assert_eq!(rewrite.replaced, vec![("1234", "9000"), ("123", "900")]);
+    }
+
+    #[test]
+    fn replaces_ids_at_both_ends_of_the_source() {
+        let replacer = IdReplacer::new(
+            &mapping(&[("111", "222"), ("333", "444")]),
+            &AnimationFilter::new(),
+        )
+        .unwrap();
+
+        let rewrite = replacer.replace("111 -- 333").unwrap();
+        assert_eq!(rewrite.source, "222 -- 444");
+        assert_eq!(rewrite.replaced, vec![("111", "222"), ("333", "444")]);
+
+        assert_eq!(replacer.replace("111").unwrap().source, "222");
+    }
+
+    #[test]
+    fn leaves_filtered_ids_alone() {
+        let filter = AnimationFilter::new().deny_ids([111]);
+        let replacer =
+            IdReplacer::new(&mapping(&[("111", "222"), ("333", "444")]), &filter).unwrap();
+
+        assert_eq!(replacer.replace("local id = 111"), None);
+
+        let rewrite = replacer.replace("local a, b = 111, 333").unwrap();
+        assert_eq!(rewrite.source, "local a, b = 111, 444");
+        assert_eq!(rewrite.replaced, vec![("333", "444")]);
+    }
+
+    #[test]
+    fn empty_mapping_replaces_nothing() {
+        let replacer = IdReplacer::new(&HashMap::new(), &AnimationFilter::new()).unwrap();
+
+        assert!(replacer.is_empty());
+        assert_eq!(replacer.replace("local id = 123"), None);
+        assert_eq!(
+            replace_ids_in_source("local id = 123", &HashMap::new(), &AnimationFilter::new())
+                .unwrap(),
+            "local id = 123"
+        );
     }
 }
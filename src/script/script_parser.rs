@@ -1,4 +1,6 @@
 use crate::StudioParser;
+use crate::asset::AssetType;
+use crate::progress::{ProgressEvent, ScanSource};
 use rbx_dom_weak::types::Variant;
 use regex::Regex;
 use roboat::assetdelivery::AssetBatchResponse;
@@ -14,7 +16,26 @@ impl StudioParser {
     /// * Requires a cookie
     /// * Batch API does hang sometimes, fixed that with retries and 3 second timeout.
     pub async fn all_animations_in_scripts(&mut self) -> anyhow::Result<Vec<AssetBatchResponse>> {
-        let script_refs = self.get_script_refs();
+        self.all_assets_in_scripts(AssetType::Animation).await
+    }
+
+    /// Same as `all_animations_in_scripts` for any asset type, e.g. sounds played from scripts.
+    pub async fn all_assets_in_scripts(
+        &self,
+        asset_type: AssetType,
+    ) -> anyhow::Result<Vec<AssetBatchResponse>> {
+        let id_list = self.script_asset_ids();
+        self.progress.emit(ProgressEvent::ScanFound {
+            source: ScanSource::Scripts,
+            asset_type,
+            count: id_list.len(),
+        });
+        self.fetch_assets(id_list, asset_type).await
+    }
+
+    /// Collects the unique `rbxassetid://` IDs referenced in the sources of the filtered scripts.
+    pub fn script_asset_ids(&self) -> Vec<u64> {
+        let script_refs = self.filtered_script_refs();
         let pattern = Regex::new(r"rbxassetid://(\d{5,})").unwrap();
 
         // Collect and deduplicate all IDs from all scripts
@@ -25,19 +46,17 @@ impl StudioParser {
                     instance.properties.get(&Ustr::from("Source"))
                 {
                     let ids_in_script = pattern
-                        .find_iter(source)
-                        .filter_map(|m| m.as_str().parse::<u64>().ok());
+                        .captures_iter(source)
+                        .filter_map(|captures| captures[1].parse::<u64>().ok());
 
                     all_ids.extend(ids_in_script);
                 }
             }
         }
 
-        // Convert to Vec and fetch assets
         let mut id_list: Vec<u64> = all_ids.into_iter().collect();
         id_list.sort();
-        println!("Got all animations from scripts: {}", id_list.len());
-        self.fetch_animation_assets(id_list).await
+        id_list
     }
 
     /// Gets references to all script instances in the DOM.
@@ -53,6 +72,28 @@ impl StudioParser {
             .map(|instance| instance.referent())
             .collect()
     }
+
+    /// Gets references to the scripts allowed by the parser's filter (script name and path).
+    pub fn filtered_script_refs(&self) -> Vec<rbx_dom_weak::types::Ref> {
+        if !self.filter.has_script_rules() {
+            return self.get_script_refs();
+        }
+
+        self.dom
+            .descendants()
+            .filter(|instance| {
+                matches!(
+                    instance.class.as_str(),
+                    "Script" | "LocalScript" | "ModuleScript"
+                )
+            })
+            .filter(|instance| {
+                self.filter
+                    .allows_script(&instance.name, &self.instance_path(instance.referent()))
+            })
+            .map(|instance| instance.referent())
+            .collect()
+    }
 }
 
 mod internal {}
@@ -0,0 +1,122 @@
+use crate::StudioParser;
+use rbx_dom_weak::types::{Ref, Variant};
+use ustr::Ustr;
+
+/// StringValue names the default Animate LocalScript reads its animations from.
+pub const ANIMATE_SLOTS: [&str; 20] = [
+    "idle",
+    "walk",
+    "run",
+    "jump",
+    "fall",
+    "climb",
+    "sit",
+    "swim",
+    "swimidle",
+    "toolnone",
+    "toolslash",
+    "toollunge",
+    "wave",
+    "point",
+    "dance",
+    "dance2",
+    "dance3",
+    "laugh",
+    "cheer",
+    "mood",
+];
+
+/// Animation IDs shipped in Roblox's default R6 and R15 Animate scripts.
+/// These are owned by Roblox and play in every game, so they never need a reupload.
+pub const ROBLOX_DEFAULT_ANIMATIONS: [u64; 53] = [
+    // R15
+    507766388, 507766666, 507766951, 507777826, 507767714, 507765000, 507767968, 507765644,
+    2506281703, 507784897, 481825862, 507768375, 522635514, 522638767, 507770239, 507770453,
+    507771019, 507771955, 507772104, 507776043, 507776720, 507776879, 507777268, 507777451,
+    507777623, 507770818, 507770677, 7715096377, 913402848, 913376220, 913384386, 913389285,
+    // R6
+    180435571, 180435792, 180426354, 125750702, 180436334, 182393478, 180436148, 178130996,
+    129967390, 129967478, 128777973, 128853357, 182435998, 182491037, 182491065, 182436842,
+    182491248, 182491277, 182436935, 182491368, 182491423,
+];
+
+/// An Animation inside one of the Animate script's StringValue slots.
+#[derive(Debug, Clone)]
+pub struct AnimateSlot {
+    pub referent: Ref,
+    /// Readable location such as `Animate.walk.WalkAnim`.
+    pub path: String,
+    pub slot: String,
+    pub animation_id: u64,
+    pub is_roblox_default: bool,
+}
+
+/// Returns true if the animation is one of Roblox's default Animate script animations.
+pub fn is_roblox_default_animation(animation_id: u64) -> bool {
+    ROBLOX_DEFAULT_ANIMATIONS.contains(&animation_id)
+}
+
+impl StudioParser {
+    /// Finds every Animation under the StringValue slots (idle, walk, run...) of Animate
+    /// LocalScripts, such as the one in StarterCharacterScripts.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// for slot in parser.animate_slots() {
+    ///     println!("{} -> {}", slot.path, slot.animation_id);
+    /// }
+    /// ```
+    pub fn animate_slots(&self) -> Vec<AnimateSlot> {
+        let animation_id_key = Ustr::from("AnimationId");
+        let mut slots = Vec::new();
+
+        let animate_scripts = self
+            .dom
+            .descendants()
+            .filter(|instance| instance.class == "LocalScript" && instance.name == "Animate");
+
+        for script in animate_scripts {
+            for slot_ref in script.children() {
+                let Some(slot) = self.dom.get_by_ref(*slot_ref) else {
+                    continue;
+                };
+                if slot.class != "StringValue"
+                    || !ANIMATE_SLOTS.contains(&slot.name.to_lowercase().as_str())
+                {
+                    continue;
+                }
+
+                for animation_ref in slot.children() {
+                    let Some(animation) = self.dom.get_by_ref(*animation_ref) else {
+                        continue;
+                    };
+                    if animation.class != "Animation" {
+                        continue;
+                    }
+
+                    let animation_id = match animation.properties.get(&animation_id_key) {
+                        Some(Variant::ContentId(content_id)) => content_id
+                            .as_str()
+                            .trim_start_matches(|c: char| !c.is_ascii_digit())
+                            .parse::<u64>()
+                            .ok(),
+                        _ => None,
+                    };
+
+                    if let Some(animation_id) = animation_id {
+                        slots.push(AnimateSlot {
+                            referent: animation.referent(),
+                            path: format!("{}.{}.{}", script.name, slot.name, animation.name),
+                            slot: slot.name.clone(),
+                            animation_id,
+                            is_roblox_default: is_roblox_default_animation(animation_id),
+                        });
+                    }
+                }
+            }
+        }
+
+        slots
+    }
+}
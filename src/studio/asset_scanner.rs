@@ -0,0 +1,146 @@
+use crate::StudioParser;
+use crate::asset::AssetType;
+use crate::progress::{ProgressEvent, ScanSource};
+use rbx_dom_weak::types::{Ref, Variant};
+use regex::Regex;
+use roboat::assetdelivery::AssetBatchResponse;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use ustr::Ustr;
+
+/// Matches `rbxassetid://123`, `http://www.roblox.com/asset/?id=123` and bare IDs.
+static CONTENT_ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:rbxassetid://|[?&]id=)(\d+)|^(\d+)$").unwrap());
+
+/// A content property on an instance that points at an asset.
+#[derive(Debug, Clone)]
+pub struct AssetReference {
+    pub referent: Ref,
+    pub class: String,
+    pub property: String,
+    pub asset_id: u64,
+}
+
+/// Reads the asset ID out of a content property. Built-in `rbxasset://` content is ignored.
+pub fn content_asset_id(value: &Variant) -> Option<u64> {
+    match value {
+        Variant::ContentId(content_id) => CONTENT_ID
+            .captures(content_id.as_str().trim())
+            .and_then(|captures| captures.get(1).or_else(|| captures.get(2)))
+            .and_then(|id| id.as_str().parse::<u64>().ok()),
+        _ => None,
+    }
+}
+
+impl StudioParser {
+    /// Finds every property referencing the given asset type, e.g. `Sound.SoundId` for audio.
+    /// Instances rejected by the parser's path filter are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// for reference in parser.asset_references(AssetType::Audio) {
+    ///     println!("{}.{} -> {}", reference.class, reference.property, reference.asset_id);
+    /// }
+    /// ```
+    pub fn asset_references(&self, asset_type: AssetType) -> Vec<AssetReference> {
+        let properties = asset_type.properties();
+        let mut references = Vec::new();
+
+        for instance in self.dom.descendants() {
+            for &(class, property) in properties {
+                if instance.class.as_str() != class {
+                    continue;
+                }
+
+                let asset_id = instance
+                    .properties
+                    .get(&Ustr::from(property))
+                    .and_then(content_asset_id);
+
+                if let Some(asset_id) = asset_id {
+                    references.push(AssetReference {
+                        referent: instance.referent(),
+                        class: class.to_string(),
+                        property: property.to_string(),
+                        asset_id,
+                    });
+                }
+            }
+        }
+
+        references.retain(|reference| {
+            self.filter
+                .allows_path(&self.instance_path(reference.referent))
+        });
+        references
+    }
+
+    /// Collects the unique IDs of an asset type referenced in the DOM.
+    /// For animations this includes IDs stored in value objects and attributes.
+    pub fn asset_ids(&self, asset_type: AssetType) -> Vec<u64> {
+        let mut asset_ids: Vec<u64> = self
+            .asset_references(asset_type)
+            .into_iter()
+            .map(|reference| reference.asset_id)
+            .collect();
+
+        if asset_type == AssetType::Animation {
+            asset_ids.extend(
+                self.value_animation_ids()
+                    .into_iter()
+                    .filter(|found| self.filter.allows_path(&self.instance_path(found.referent)))
+                    .map(|found| found.animation_id),
+            );
+        }
+
+        asset_ids.sort();
+        asset_ids.dedup();
+        asset_ids
+    }
+
+    /// Finds assets of the given type in the workspace and returns their metadata.
+    pub async fn workspace_assets(
+        &self,
+        asset_type: AssetType,
+    ) -> anyhow::Result<Vec<AssetBatchResponse>> {
+        let asset_ids = self.asset_ids(asset_type);
+        self.progress.emit(ProgressEvent::ScanFound {
+            source: ScanSource::Workspace,
+            asset_type,
+            count: asset_ids.len(),
+        });
+        self.fetch_assets(asset_ids, asset_type).await
+    }
+
+    /// Replaces the asset IDs of the given type in the DOM using the provided mapping.
+    /// Returns how many properties were rewritten.
+    pub fn update_game_assets(
+        &mut self,
+        asset_type: AssetType,
+        asset_mapping: &HashMap<String, String>,
+    ) -> usize {
+        let mut updated = 0;
+        for reference in self.asset_references(asset_type) {
+            if !self.filter.allows_id(reference.asset_id) {
+                continue;
+            }
+
+            let Some(new_id) = asset_mapping.get(&reference.asset_id.to_string()) else {
+                continue;
+            };
+
+            if let Some(instance) = self.dom.get_by_ref_mut(reference.referent) {
+                let rbxasset = format!("rbxassetid://{}", new_id);
+                instance.properties.insert(
+                    Ustr::from(reference.property.as_str()),
+                    Variant::ContentId(rbxasset.into()),
+                );
+                self.rewritten_ids.insert(reference.asset_id);
+                updated += 1;
+            }
+        }
+
+        updated
+    }
+}
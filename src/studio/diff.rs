@@ -0,0 +1,193 @@
+use crate::StudioParser;
+use crate::studio::inventory::InventoryEntry;
+use rbx_dom_weak::types::Variant;
+use serde::Serialize;
+use similar::TextDiff;
+use std::collections::{BTreeMap, BTreeSet};
+use ustr::Ustr;
+
+/// An instance property whose animation IDs differ between two places.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReferenceChange {
+    pub path: String,
+    pub class: String,
+    /// `AnimationId`, `Value` or `Attributes.<name>`.
+    pub property: String,
+    /// Empty when the reference only exists in the second place.
+    pub old_ids: Vec<u64>,
+    /// Empty when the reference only exists in the first place.
+    pub new_ids: Vec<u64>,
+}
+
+/// A script whose `rbxassetid://` IDs differ between two places.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptChange {
+    pub path: String,
+    pub old_ids: Vec<u64>,
+    pub new_ids: Vec<u64>,
+    /// Unified diff of the script source, `a/<path>` against `b/<path>`.
+    pub diff: String,
+}
+
+/// Animation changes between two places, sorted by instance path.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PlaceDiff {
+    pub references: Vec<ReferenceChange>,
+    pub scripts: Vec<ScriptChange>,
+}
+
+impl PlaceDiff {
+    pub fn is_empty(&self) -> bool {
+        self.references.is_empty() && self.scripts.is_empty()
+    }
+}
+
+impl StudioParser {
+    /// Compares the animation references of this place (before) with another one (after).
+    /// Instances and scripts are matched by instance path.
+    ///
+    /// * Notes
+    /// Only Animation instances, value objects, attributes and script sources are compared,
+    /// other asset types are ignored. Filters are ignored too, so the diff covers the whole
+    /// place. Siblings sharing a name share a path, so their IDs are compared together.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let before = StudioParser::builder().file_path("MyPlace.rbxl").build()?;
+    /// let after = StudioParser::builder().file_path("MyPlace.reuploaded.rbxl").build()?;
+    ///
+    /// for change in before.diff_animations(&after).references {
+    ///     println!("{} {}: {:?} -> {:?}", change.path, change.property, change.old_ids, change.new_ids);
+    /// }
+    /// ```
+    pub fn diff_animations(&self, other: &StudioParser) -> PlaceDiff {
+        let old_inventory = self.asset_inventory();
+        let new_inventory = other.asset_inventory();
+
+        let old_references = animation_references(&old_inventory);
+        let new_references = animation_references(&new_inventory);
+        let keys: BTreeSet<_> = old_references.keys().chain(new_references.keys()).collect();
+
+        let mut references = Vec::new();
+        for key in keys {
+            let old_ids = old_references.get(key).cloned().unwrap_or_default();
+            let new_ids = new_references.get(key).cloned().unwrap_or_default();
+            if old_ids != new_ids {
+                references.push(ReferenceChange {
+                    path: key.0.clone(),
+                    class: key.1.clone(),
+                    property: key.2.clone(),
+                    old_ids,
+                    new_ids,
+                });
+            }
+        }
+
+        let old_script_ids = script_ids(&old_inventory);
+        let new_script_ids = script_ids(&new_inventory);
+        let old_sources = self.script_sources();
+        let new_sources = other.script_sources();
+        let paths: BTreeSet<_> = old_script_ids.keys().chain(new_script_ids.keys()).collect();
+
+        let mut scripts = Vec::new();
+        for path in paths {
+            let old_ids = old_script_ids.get(path).cloned().unwrap_or_default();
+            let new_ids = new_script_ids.get(path).cloned().unwrap_or_default();
+            if old_ids == new_ids {
+                continue;
+            }
+
+            let old_source = old_sources
+                .get(path)
+                .map(String::as_str)
+                .unwrap_or_default();
+            let new_source = new_sources
+                .get(path)
+                .map(String::as_str)
+                .unwrap_or_default();
+            let diff = TextDiff::from_lines(old_source, new_source)
+                .unified_diff()
+                .context_radius(3)
+                .header(&format!("a/{}", path), &format!("b/{}", path))
+                .to_string();
+
+            scripts.push(ScriptChange {
+                path: path.clone(),
+                old_ids,
+                new_ids,
+                diff,
+            });
+        }
+
+        PlaceDiff {
+            references,
+            scripts,
+        }
+    }
+
+    /// Source of every script keyed by instance path. When several scripts share a path
+    /// their sources are joined in DOM order.
+    fn script_sources(&self) -> BTreeMap<String, String> {
+        let source_key = Ustr::from("Source");
+        let mut sources: BTreeMap<String, String> = BTreeMap::new();
+
+        for script_ref in self.get_script_refs() {
+            let Some(instance) = self.dom.get_by_ref(script_ref) else {
+                continue;
+            };
+            if let Some(Variant::String(source)) = instance.properties.get(&source_key) {
+                let joined = sources.entry(self.instance_path(script_ref)).or_default();
+                if !joined.is_empty() {
+                    joined.push('\n');
+                }
+                joined.push_str(source);
+            }
+        }
+
+        sources
+    }
+}
+
+/// Sorted animation IDs per (path, class, property), leaving out scripts and other content
+/// such as sounds and meshes.
+fn animation_references(
+    entries: &[InventoryEntry],
+) -> BTreeMap<(String, String, String), Vec<u64>> {
+    let mut references: BTreeMap<_, Vec<u64>> = BTreeMap::new();
+    for entry in entries {
+        let is_animation = entry.class == "Animation"
+            || entry.property == "Value"
+            || entry.property.starts_with("Attributes.");
+        if !is_animation {
+            continue;
+        }
+        references
+            .entry((
+                entry.path.clone(),
+                entry.class.clone(),
+                entry.property.clone(),
+            ))
+            .or_default()
+            .push(entry.asset_id);
+    }
+    for ids in references.values_mut() {
+        ids.sort();
+    }
+    references
+}
+
+/// Sorted, deduplicated IDs referenced in script sources, keyed by script path.
+fn script_ids(entries: &[InventoryEntry]) -> BTreeMap<String, Vec<u64>> {
+    let mut ids: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+    for entry in entries.iter().filter(|entry| entry.property == "Source") {
+        ids.entry(entry.path.clone())
+            .or_default()
+            .push(entry.asset_id);
+    }
+    for script_ids in ids.values_mut() {
+        script_ids.sort();
+        script_ids.dedup();
+    }
+    ids
+}
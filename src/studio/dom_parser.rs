@@ -1,14 +1,23 @@
-use crate::StudioParser;
+use crate::animation::resolver::PlaceResolver;
+use crate::animation::uploader::{DEFAULT_BATCH_CONCURRENCY, DEFAULT_NAME_TEMPLATE};
+use crate::asset::AssetType;
+use crate::config::Endpoints;
+use crate::places::PlaceCache;
+use crate::progress::{Progress, ProgressSender};
+use crate::rate_limit::RateLimits;
+use crate::{AnimationFilter, RetryPolicy, Roblosecurity, StudioParser};
 use rbx_binary::from_reader;
-use rbx_types::Variant;
-use regex::Regex;
+use rbx_dom_weak::types::Ref;
 use roboat::assetdelivery::AssetBatchResponse;
+use std::collections::HashSet;
 use std::fs::File;
-use std::path::Path;
-use ustr::Ustr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
 impl StudioParser {
     /// Finds Animation instances in the workspace and returns their metadata.
+    /// Animation IDs stored in value objects and attributes are included as well.
     ///
     /// # Examples
     ///
@@ -20,24 +29,26 @@ impl StudioParser {
     /// let animations = parser.workspace_animations().await?;
     /// ```
     pub async fn workspace_animations(&self) -> anyhow::Result<Vec<AssetBatchResponse>> {
-        let re = Regex::new(r"\d+").unwrap();
-
-        let mut asset_ids: Vec<u64> = self
-            .dom
-            .descendants()
-            .filter(|instance| instance.class == "Animation")
-            .filter_map(
-                |instance| match instance.properties.get(&Ustr::from("AnimationId")) {
-                    Some(Variant::ContentId(content_id)) => re
-                        .find(content_id.as_str())
-                        .and_then(|mat| mat.as_str().parse::<u64>().ok()),
-                    _ => None,
-                },
-            )
-            .collect();
-
-        asset_ids.sort();
-        self.fetch_animation_assets(asset_ids).await
+        self.workspace_assets(AssetType::Animation).await
+    }
+
+    /// Returns the slash separated path of an instance below the DataModel,
+    /// e.g. `ReplicatedStorage/Weapons/Sword/Slash`.
+    pub fn instance_path(&self, referent: Ref) -> String {
+        let root_ref = self.dom.root_ref();
+        let mut names = Vec::new();
+        let mut current = referent;
+
+        while current != root_ref {
+            let Some(instance) = self.dom.get_by_ref(current) else {
+                break;
+            };
+            names.push(instance.name.as_str());
+            current = instance.parent();
+        }
+
+        names.reverse();
+        names.join("/")
     }
 
     /// Creates a builder for fluent configuration with file path and authentication.
@@ -56,10 +67,24 @@ impl StudioParser {
 }
 
 /// Builder for creating StudioParser instances with optional authentication.
-#[derive(Debug, Default)]
+///
+/// * Notes
+/// Parsers built from clones of one builder share its rate limiters and place resolver, so
+/// several places processed in one run stay within one set of API limits.
+#[derive(Debug, Clone, Default)]
 pub struct StudioParserBuilder {
     file_path: Option<String>,
-    roblosecurity: Option<String>,
+    roblosecurity: Option<Roblosecurity>,
+    exclude_default_animations: bool,
+    filter: AnimationFilter,
+    retry_policy: RetryPolicy,
+    cancellation: CancellationToken,
+    progress: Progress,
+    endpoints: Endpoints,
+    name_template: Option<String>,
+    rate_limits: Arc<RateLimits>,
+    place_resolver: Arc<PlaceResolver>,
+    batch_concurrency: Option<usize>,
 }
 
 impl StudioParserBuilder {
@@ -74,11 +99,79 @@ impl StudioParserBuilder {
 
     /// Sets the Roblosecurity cookie for API authentication.
     /// Required for animation validation and re-uploading features.
-    pub fn roblosecurity<S: Into<String>>(mut self, roblosecurity: S) -> Self {
+    pub fn roblosecurity<S: Into<Roblosecurity>>(mut self, roblosecurity: S) -> Self {
         self.roblosecurity = Some(roblosecurity.into());
         self
     }
 
+    /// Skips Roblox's default Animate script animations (idle, walk, run...) when fetching
+    /// animations for reupload. They are owned by Roblox and already work in every game.
+    pub fn exclude_default_animations(mut self, exclude: bool) -> Self {
+        self.exclude_default_animations = exclude;
+        self
+    }
+
+    /// Limits which animations are scanned, reuploaded and replaced.
+    pub fn filter(mut self, filter: AnimationFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Sets how failed metadata fetches, downloads and uploads are retried.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets the token that stops fetching and uploading when cancelled, e.g. on Ctrl-C.
+    pub fn cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Sends scan, fetch, upload and apply events to a channel instead of only printing.
+    pub fn progress(mut self, sender: ProgressSender) -> Self {
+        self.progress = Progress::new(sender);
+        self
+    }
+
+    /// Overrides the base URLs of the APIs this crate calls itself.
+    pub fn endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Names reuploaded animations from a template with `{id}` and `{name}` placeholders.
+    pub fn name_template<S: Into<String>>(mut self, template: S) -> Self {
+        self.name_template = Some(template.into());
+        self
+    }
+
+    /// Starts from known creator -> place IDs, e.g. loaded from an earlier run's cache with
+    /// overrides from the config.
+    pub fn place_cache(mut self, place_cache: PlaceCache) -> Self {
+        self.place_resolver = Arc::new(PlaceResolver::new(place_cache));
+        self
+    }
+
+    /// Shares rate limiters with other parsers or uploaders.
+    pub fn rate_limits(mut self, rate_limits: Arc<RateLimits>) -> Self {
+        self.rate_limits = rate_limits;
+        self
+    }
+
+    /// Shares looked up creators and places with other parsers or uploaders.
+    pub fn place_resolver(mut self, place_resolver: Arc<PlaceResolver>) -> Self {
+        self.place_resolver = place_resolver;
+        self
+    }
+
+    /// Sets how many metadata batches are fetched at once (default 4).
+    pub fn batch_concurrency(mut self, batch_concurrency: usize) -> Self {
+        self.batch_concurrency = Some(batch_concurrency);
+        self
+    }
+
     /// Builds the StudioParser. File path is required.
     pub fn build(self) -> Result<StudioParser, anyhow::Error> {
         let file_path = self
@@ -97,6 +190,24 @@ impl StudioParserBuilder {
         Ok(StudioParser {
             roblosecurity: self.roblosecurity,
             dom,
+            file_path: Some(PathBuf::from(expanded_path.as_ref())),
+            exclude_default_animations: self.exclude_default_animations,
+            filter: self.filter,
+            rate_limits: self.rate_limits,
+            retry_policy: self.retry_policy,
+            cancellation: self.cancellation,
+            progress: self.progress,
+            endpoints: self.endpoints,
+            name_template: self
+                .name_template
+                .unwrap_or_else(|| DEFAULT_NAME_TEMPLATE.to_string()),
+            place_resolver: self.place_resolver,
+            batch_concurrency: self
+                .batch_concurrency
+                .unwrap_or(DEFAULT_BATCH_CONCURRENCY)
+                .max(1),
+            skipped_values: Vec::new(),
+            rewritten_ids: HashSet::new(),
         })
     }
 }
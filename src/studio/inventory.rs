@@ -0,0 +1,172 @@
+use crate::StudioParser;
+use crate::asset::AssetType;
+use crate::studio::asset_scanner::content_asset_id;
+use crate::studio::value_ids::{VALUE_CLASSES, parse_value_id};
+use log::warn;
+use rbx_dom_weak::types::Variant;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use ustr::Ustr;
+
+/// One asset reference found in a place.
+#[derive(Debug, Clone, Serialize)]
+pub struct InventoryEntry {
+    pub asset_id: u64,
+    pub class: String,
+    /// The property holding the ID: `SoundId`, `Value`, `Attributes.AnimationId`, `Source`...
+    pub property: String,
+    pub path: String,
+    /// Filled in by `annotate_inventory`, e.g. `animation` or `asset type 10`.
+    pub asset_type: Option<String>,
+    /// Filled in by `annotate_inventory`, e.g. `user:1` or `group:2`.
+    pub creator: Option<String>,
+}
+
+impl StudioParser {
+    /// Walks the DOM and every script source and lists each asset ID referenced, without
+    /// changing anything. Filters are ignored so the inventory covers the whole place.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let parser = StudioParser::builder().file_path("MyPlace.rbxl").build()?;
+    /// let entries = parser.asset_inventory();
+    /// println!("{}", inventory_to_json(&entries)?);
+    /// ```
+    pub fn asset_inventory(&self) -> Vec<InventoryEntry> {
+        let script_pattern = Regex::new(r"rbxassetid://(\d{5,})").unwrap();
+        let value_key = Ustr::from("Value");
+        let source_key = Ustr::from("Source");
+        let mut entries = Vec::new();
+
+        for instance in self.dom.descendants() {
+            let path = self.instance_path(instance.referent());
+            let class = instance.class.as_str();
+            let mut push = |asset_id: u64, property: String| {
+                entries.push(InventoryEntry {
+                    asset_id,
+                    class: class.to_string(),
+                    property,
+                    path: path.clone(),
+                    asset_type: None,
+                    creator: None,
+                });
+            };
+
+            for (property, value) in &instance.properties {
+                match value {
+                    Variant::ContentId(_) => {
+                        if let Some(asset_id) = content_asset_id(value) {
+                            push(asset_id, property.to_string());
+                        }
+                    }
+                    Variant::Attributes(attributes) => {
+                        for (name, attribute) in attributes.iter() {
+                            if let Some((asset_id, _)) = parse_value_id(attribute, name) {
+                                push(asset_id, format!("Attributes.{}", name));
+                            }
+                        }
+                    }
+                    Variant::String(source) if *property == source_key => {
+                        for captures in script_pattern.captures_iter(source) {
+                            if let Ok(asset_id) = captures[1].parse::<u64>() {
+                                push(asset_id, property.to_string());
+                            }
+                        }
+                    }
+                    _ if *property == value_key && VALUE_CLASSES.contains(&class) => {
+                        if let Some((asset_id, _)) = parse_value_id(value, &instance.name) {
+                            push(asset_id, property.to_string());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        entries
+    }
+
+    /// Fills in the asset type (from the batch metadata API) and creator (from the asset
+    /// info API) of each entry. Requires a cookie.
+    ///
+    /// * Notes
+    /// Each unique ID is looked up once. IDs the APIs can't resolve keep `None`.
+    pub async fn annotate_inventory(&self, entries: &mut [InventoryEntry]) -> anyhow::Result<()> {
+        let uploader = self.animation_uploader()?;
+
+        let unique_ids: HashSet<u64> = entries.iter().map(|entry| entry.asset_id).collect();
+        let mut asset_ids: Vec<u64> = unique_ids.into_iter().collect();
+        asset_ids.sort();
+
+        let mut asset_types: HashMap<u64, String> = HashMap::new();
+        for response in uploader.fetch_animation_assets(asset_ids.clone()).await? {
+            let asset_id = response
+                .request_id
+                .as_ref()
+                .and_then(|id| id.parse::<u64>().ok());
+            let type_id = response.asset_type_id.map(|type_id| type_id as u64);
+
+            if let (Some(asset_id), Some(type_id)) = (asset_id, type_id) {
+                let asset_type = match AssetType::from_asset_type_id(type_id) {
+                    Some(asset_type) => asset_type.to_string(),
+                    None => format!("asset type {}", type_id),
+                };
+                asset_types.insert(asset_id, asset_type);
+            }
+        }
+
+        let mut creators: HashMap<u64, String> = HashMap::new();
+        for (asset_id, creator) in uploader.asset_creators(&asset_ids).await {
+            match creator {
+                Ok(creator) => {
+                    creators.insert(asset_id, creator.to_string());
+                }
+                Err(e) => {
+                    warn!(asset_id = asset_id; "Couldn't resolve creator of asset: {}", e);
+                }
+            }
+        }
+
+        for entry in entries.iter_mut() {
+            entry.asset_type = asset_types.get(&entry.asset_id).cloned();
+            entry.creator = creators.get(&entry.asset_id).cloned();
+        }
+
+        Ok(())
+    }
+}
+
+/// Serializes an inventory as a pretty printed JSON array.
+pub fn inventory_to_json(entries: &[InventoryEntry]) -> anyhow::Result<String> {
+    Ok(serde_json::to_string_pretty(entries)?)
+}
+
+/// Serializes an inventory as CSV with a header row.
+pub fn inventory_to_csv(entries: &[InventoryEntry]) -> String {
+    let mut csv = String::from("asset_id,class,property,path,asset_type,creator\n");
+
+    for entry in entries {
+        let row = [
+            entry.asset_id.to_string(),
+            csv_field(&entry.class),
+            csv_field(&entry.property),
+            csv_field(&entry.path),
+            csv_field(entry.asset_type.as_deref().unwrap_or_default()),
+            csv_field(entry.creator.as_deref().unwrap_or_default()),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -1,2 +1,8 @@
+pub mod animate_script;
+pub mod asset_scanner;
+pub mod diff;
 pub mod dom_parser;
+pub mod inventory;
+pub mod value_ids;
+pub mod verify;
 pub mod workplace_editor;
@@ -0,0 +1,463 @@
+use crate::StudioParser;
+use log::warn;
+use rbx_dom_weak::types::{Ref, Variant};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use ustr::Ustr;
+
+/// Value object classes whose `Value` property can hold an animation ID.
+pub const VALUE_CLASSES: [&str; 3] = ["StringValue", "IntValue", "NumberValue"];
+
+/// Matches `rbxassetid://123` and `http://www.roblox.com/asset/?id=123` style strings.
+static PREFIXED_ID: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:rbxassetid://|https?://(?:www\.)?roblox\.com/asset/?\?id=)(\d+)$").unwrap()
+});
+
+/// Where an animation ID was found outside of an Animation instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueLocation {
+    /// The `Value` property of a StringValue, IntValue or NumberValue.
+    Value,
+    /// An instance attribute, keyed by the attribute name.
+    Attribute(String),
+}
+
+/// How an animation ID is stored, so it can be written back in the same shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdFormat {
+    /// A string where the ID follows a prefix, e.g. `rbxassetid://123`.
+    PrefixedString,
+    /// A string holding only the digits of the ID.
+    PlainString,
+    /// A numeric value (IntValue, NumberValue or a number attribute).
+    Number,
+}
+
+/// An animation ID stored in a value object or an attribute.
+#[derive(Debug, Clone)]
+pub struct ValueAnimationId {
+    pub referent: Ref,
+    pub location: ValueLocation,
+    pub format: IdFormat,
+    pub animation_id: u64,
+}
+
+/// A mapped ID in a value object or an attribute that couldn't hold the new ID, so the old
+/// ID was kept. E.g. an IntValue can't hold IDs above `i32::MAX`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedValue {
+    pub path: String,
+    /// `Value` or `Attributes.<name>`, as in the inventory.
+    pub property: String,
+    pub asset_id: u64,
+    pub new_id: u64,
+}
+
+/// Reads an animation ID out of a value.
+///
+/// * Notes
+/// Prefixed strings are always treated as asset IDs. Plain strings and numbers are only
+/// picked up when `name` (the value object or attribute name) mentions "anim", so values
+/// such as `Coins = 100000` are left alone.
+pub fn parse_value_id(value: &Variant, name: &str) -> Option<(u64, IdFormat)> {
+    let named_like_animation = name.to_lowercase().contains("anim");
+
+    match value {
+        Variant::String(text) => {
+            let text = text.trim();
+            if let Some(captures) = PREFIXED_ID.captures(text) {
+                return captures[1]
+                    .parse::<u64>()
+                    .ok()
+                    .map(|id| (id, IdFormat::PrefixedString));
+            }
+
+            if named_like_animation && !text.is_empty() && text.chars().all(|c| c.is_ascii_digit())
+            {
+                return text
+                    .parse::<u64>()
+                    .ok()
+                    .map(|id| (id, IdFormat::PlainString));
+            }
+            None
+        }
+        Variant::Int64(number) if named_like_animation && *number > 0 => {
+            Some((*number as u64, IdFormat::Number))
+        }
+        Variant::Int32(number) if named_like_animation && *number > 0 => {
+            Some((*number as u64, IdFormat::Number))
+        }
+        Variant::Float64(number)
+            if named_like_animation && *number > 0.0 && number.fract() == 0.0 =>
+        {
+            Some((*number as u64, IdFormat::Number))
+        }
+        Variant::Float32(number)
+            if named_like_animation && *number > 0.0 && number.fract() == 0.0 =>
+        {
+            Some((*number as u64, IdFormat::Number))
+        }
+        _ => None,
+    }
+}
+
+/// Builds the replacement value for `value`, keeping its variant type and string prefix.
+/// Returns None when the variant can't hold `new_id` exactly, e.g. an Int32 or a Float32
+/// that would round it.
+pub fn replace_value_id(value: &Variant, format: IdFormat, new_id: u64) -> Option<Variant> {
+    match (value, format) {
+        (Variant::String(text), IdFormat::PrefixedString) => {
+            let trimmed = text.trim();
+            let digits_start = trimmed.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+            Some(Variant::String(format!(
+                "{}{}",
+                &trimmed[..digits_start],
+                new_id
+            )))
+        }
+        (Variant::String(_), IdFormat::PlainString) => Some(Variant::String(new_id.to_string())),
+        (Variant::Int64(_), IdFormat::Number) => Some(Variant::Int64(new_id as i64)),
+        (Variant::Int32(_), IdFormat::Number) => i32::try_from(new_id).ok().map(Variant::Int32),
+        (Variant::Float64(_), IdFormat::Number) => {
+            let number = new_id as f64;
+            (number as u64 == new_id).then_some(Variant::Float64(number))
+        }
+        (Variant::Float32(_), IdFormat::Number) => {
+            let number = new_id as f32;
+            (number as u64 == new_id).then_some(Variant::Float32(number))
+        }
+        _ => None,
+    }
+}
+
+impl StudioParser {
+    /// Finds animation IDs stored in StringValue/IntValue/NumberValue objects and in
+    /// instance attributes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// for found in parser.value_animation_ids() {
+    ///     println!("{:?} -> {}", found.location, found.animation_id);
+    /// }
+    /// ```
+    pub fn value_animation_ids(&self) -> Vec<ValueAnimationId> {
+        let value_key = Ustr::from("Value");
+        let attributes_key = Ustr::from("Attributes");
+        let mut found = Vec::new();
+
+        for instance in self.dom.descendants() {
+            if VALUE_CLASSES.contains(&instance.class.as_str()) {
+                if let Some(value) = instance.properties.get(&value_key) {
+                    if let Some((animation_id, format)) = parse_value_id(value, &instance.name) {
+                        found.push(ValueAnimationId {
+                            referent: instance.referent(),
+                            location: ValueLocation::Value,
+                            format,
+                            animation_id,
+                        });
+                    }
+                }
+            }
+
+            if let Some(Variant::Attributes(attributes)) = instance.properties.get(&attributes_key)
+            {
+                for (name, value) in attributes.iter() {
+                    if let Some((animation_id, format)) = parse_value_id(value, name) {
+                        found.push(ValueAnimationId {
+                            referent: instance.referent(),
+                            location: ValueLocation::Attribute(name.clone()),
+                            format,
+                            animation_id,
+                        });
+                    }
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Rewrites animation IDs in value objects and attributes using the provided mapping.
+    /// Returns how many values were rewritten.
+    ///
+    /// * Notes
+    /// Values that can't hold the new ID keep the old one; each is logged and added to
+    /// `skipped_values`.
+    pub fn update_value_animations(
+        &mut self,
+        animation_mapping: &HashMap<String, String>,
+    ) -> usize {
+        let mut updated = 0;
+        let value_key = Ustr::from("Value");
+        let attributes_key = Ustr::from("Attributes");
+
+        let mapped: Vec<(ValueAnimationId, u64)> = self
+            .value_animation_ids()
+            .into_iter()
+            .filter(|found| self.filter.allows_id(found.animation_id))
+            .filter_map(|found| {
+                let new_id = animation_mapping
+                    .get(&found.animation_id.to_string())
+                    .and_then(|id| id.parse::<u64>().ok())?;
+                Some((found, new_id))
+            })
+            .collect();
+
+        // value_animation_ids lists the IDs of one instance next to each other
+        for group in mapped.chunk_by(|(a, _), (b, _)| a.referent == b.referent) {
+            let referent = group[0].0.referent;
+            let path = self.instance_path(referent);
+            if !self.filter.allows_path(&path) {
+                continue;
+            }
+            let Some(instance) = self.dom.get_by_ref_mut(referent) else {
+                continue;
+            };
+
+            // Copied once per instance and written back after all its attributes are replaced
+            let has_attribute = group
+                .iter()
+                .any(|(found, _)| matches!(found.location, ValueLocation::Attribute(_)));
+            let mut attributes = match instance.properties.get(&attributes_key) {
+                Some(Variant::Attributes(attributes)) if has_attribute => Some(attributes.clone()),
+                _ => None,
+            };
+            let mut attributes_changed = false;
+
+            for (found, new_id) in group {
+                let new_id = *new_id;
+                let (property, replaced) = match &found.location {
+                    ValueLocation::Value => {
+                        let replacement = instance
+                            .properties
+                            .get(&value_key)
+                            .and_then(|value| replace_value_id(value, found.format, new_id));
+
+                        let replaced = replacement.is_some();
+                        if let Some(replacement) = replacement {
+                            instance.properties.insert(value_key, replacement);
+                        }
+                        ("Value".to_string(), replaced)
+                    }
+                    ValueLocation::Attribute(name) => {
+                        let replacement = attributes
+                            .as_ref()
+                            .and_then(|attributes| attributes.get(name.as_str()))
+                            .and_then(|value| replace_value_id(value, found.format, new_id));
+
+                        let replaced = replacement.is_some();
+                        if let (Some(attributes), Some(replacement)) =
+                            (attributes.as_mut(), replacement)
+                        {
+                            attributes.insert(name.clone(), replacement);
+                            attributes_changed = true;
+                        }
+                        (format!("Attributes.{}", name), replaced)
+                    }
+                };
+
+                if replaced {
+                    self.rewritten_ids.insert(found.animation_id);
+                    updated += 1;
+                    continue;
+                }
+
+                warn!(
+                    asset_id = found.animation_id, new_asset_id = new_id, path = path.as_str(), property = property.as_str();
+                    "Value can't hold the new ID, keeping the old one"
+                );
+                self.skipped_values.push(SkippedValue {
+                    path: path.clone(),
+                    property,
+                    asset_id: found.animation_id,
+                    new_id,
+                });
+            }
+
+            if let Some(attributes) = attributes.filter(|_| attributes_changed) {
+                instance
+                    .properties
+                    .insert(attributes_key, Variant::Attributes(attributes));
+            }
+        }
+
+        updated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rbx_dom_weak::types::Attributes;
+    use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    use std::fs::File;
+
+    /// Writes a place with an IntValue and a Folder with number attributes, then loads it.
+    fn parser_with_values(name: &str) -> StudioParser {
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let storage = dom.insert(
+            dom.root_ref(),
+            InstanceBuilder::new("ReplicatedStorage").with_name("ReplicatedStorage"),
+        );
+        dom.insert(
+            storage,
+            InstanceBuilder::new("IntValue")
+                .with_name("WalkAnim")
+                .with_property("Value", Variant::Int64(1000)),
+        );
+        dom.insert(
+            storage,
+            InstanceBuilder::new("Folder")
+                .with_name("Config")
+                .with_property(
+                    "Attributes",
+                    Variant::Attributes(
+                        Attributes::new()
+                            .with("RunAnim", Variant::Int32(2000))
+                            .with("JumpAnimation", Variant::Float32(3000.0))
+                            .with("FallAnimation", Variant::Float64(4000.0)),
+                    ),
+                ),
+        );
+
+        let path = std::env::temp_dir().join(format!(
+            "animation-replace-{}-{}.rbxl",
+            name,
+            std::process::id()
+        ));
+        let root_children = dom.get_by_ref(dom.root_ref()).unwrap().children();
+        rbx_binary::to_writer(File::create(&path).unwrap(), &dom, root_children).unwrap();
+        let parser = StudioParser::builder().file_path(&path).build().unwrap();
+        let _ = std::fs::remove_file(&path);
+        parser
+    }
+
+    #[test]
+    fn parses_prefixed_and_named_ids() {
+        assert_eq!(
+            parse_value_id(&Variant::String("rbxassetid://123".into()), "Coins"),
+            Some((123, IdFormat::PrefixedString))
+        );
+        assert_eq!(
+            parse_value_id(
+                &Variant::String("http://www.roblox.com/asset/?id=456".into()),
+                "Coins"
+            ),
+            Some((456, IdFormat::PrefixedString))
+        );
+        assert_eq!(
+            parse_value_id(&Variant::String("789".into()), "IdleAnim"),
+            Some((789, IdFormat::PlainString))
+        );
+        assert_eq!(
+            parse_value_id(&Variant::Int32(789), "IdleAnim"),
+            Some((789, IdFormat::Number))
+        );
+        assert_eq!(
+            parse_value_id(&Variant::Float32(789.0), "IdleAnim"),
+            Some((789, IdFormat::Number))
+        );
+    }
+
+    #[test]
+    fn ignores_numbers_not_named_like_animations() {
+        assert_eq!(parse_value_id(&Variant::Int64(100000), "Coins"), None);
+        assert_eq!(
+            parse_value_id(&Variant::String("100000".into()), "Coins"),
+            None
+        );
+        assert_eq!(parse_value_id(&Variant::Float64(1.5), "IdleAnim"), None);
+        assert_eq!(parse_value_id(&Variant::Int32(-5), "IdleAnim"), None);
+    }
+
+    #[test]
+    fn replaces_keeping_the_value_shape() {
+        assert_eq!(
+            replace_value_id(
+                &Variant::String(" rbxassetid://123 ".into()),
+                IdFormat::PrefixedString,
+                456
+            ),
+            Some(Variant::String("rbxassetid://456".into()))
+        );
+        assert_eq!(
+            replace_value_id(&Variant::Int64(123), IdFormat::Number, 456),
+            Some(Variant::Int64(456))
+        );
+        assert_eq!(
+            replace_value_id(&Variant::Float64(123.0), IdFormat::Number, 456),
+            Some(Variant::Float64(456.0))
+        );
+    }
+
+    #[test]
+    fn refuses_values_that_would_lose_the_id() {
+        let too_large = i32::MAX as u64 + 1;
+        assert_eq!(
+            replace_value_id(&Variant::Int32(123), IdFormat::Number, too_large),
+            None
+        );
+        assert_eq!(
+            replace_value_id(&Variant::Int32(123), IdFormat::Number, i32::MAX as u64),
+            Some(Variant::Int32(i32::MAX))
+        );
+
+        // 2^24 + 1 is the first integer a Float32 rounds
+        assert_eq!(
+            replace_value_id(&Variant::Float32(123.0), IdFormat::Number, 16_777_217),
+            None
+        );
+        assert_eq!(
+            replace_value_id(&Variant::Float32(123.0), IdFormat::Number, 16_777_216),
+            Some(Variant::Float32(16_777_216.0))
+        );
+        assert_eq!(
+            replace_value_id(&Variant::Float64(123.0), IdFormat::Number, (1 << 53) + 1),
+            None
+        );
+    }
+
+    #[test]
+    fn records_values_that_keep_their_old_id() {
+        let mut parser = parser_with_values("skipped-values");
+        let mapping: HashMap<String, String> = [
+            ("1000", "5000000000"),
+            ("2000", "5000000000"),
+            ("3000", "16777217"),
+            ("4000", "5000000000"),
+        ]
+        .into_iter()
+        .map(|(old_id, new_id)| (old_id.to_string(), new_id.to_string()))
+        .collect();
+
+        assert_eq!(parser.update_value_animations(&mapping), 2);
+
+        let mut skipped: Vec<(u64, &str)> = parser
+            .skipped_values
+            .iter()
+            .map(|skipped| (skipped.asset_id, skipped.property.as_str()))
+            .collect();
+        skipped.sort();
+        assert_eq!(
+            skipped,
+            vec![
+                (2000, "Attributes.RunAnim"),
+                (3000, "Attributes.JumpAnimation")
+            ]
+        );
+        assert!(parser.rewritten_ids.contains(&1000));
+        assert!(parser.rewritten_ids.contains(&4000));
+        assert!(!parser.rewritten_ids.contains(&2000));
+        assert!(!parser.rewritten_ids.contains(&3000));
+
+        let mut ids: Vec<u64> = parser
+            .value_animation_ids()
+            .iter()
+            .map(|found| found.animation_id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec![2000, 3000, 5_000_000_000, 5_000_000_000]);
+    }
+}
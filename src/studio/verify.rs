@@ -0,0 +1,162 @@
+use crate::StudioParser;
+use crate::studio::inventory::InventoryEntry;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// How many differences are listed in a verification error before the rest are counted.
+const MAX_LISTED: usize = 10;
+
+impl StudioParser {
+    /// Re-parses a written place and checks it matches the in-memory DOM: same instance
+    /// count, same class histogram and the same asset references. With a mapping, it also
+    /// checks that the old IDs the update functions replaced are gone everywhere the filter
+    /// allows.
+    ///
+    /// * Notes
+    /// `save_verified` runs this on the temporary file before it replaces anything, so a
+    /// failed check keeps the original untouched. Mapped IDs that were never replaced (skipped
+    /// defaults, other creators, entries carried over from other places) and values that
+    /// couldn't hold the new ID are expected to remain.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// parser.update_game_animations(&mapping);
+    /// parser.save_to_rbxl("output.rbxl")?;
+    /// parser.verify_saved_file("output.rbxl", Some(&mapping))?;
+    /// ```
+    pub fn verify_saved_file<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        animation_mapping: Option<&HashMap<String, String>>,
+    ) -> anyhow::Result<()> {
+        let file_path = file_path.as_ref();
+        let saved = StudioParser::builder()
+            .file_path(file_path)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Saved file doesn't parse back: {}", e))?;
+
+        let mut problems = Vec::new();
+
+        let expected_count = self.dom.descendants().count();
+        let saved_count = saved.dom.descendants().count();
+        if expected_count != saved_count {
+            problems.push(format!(
+                "{} instances in memory but {} in the file",
+                expected_count, saved_count
+            ));
+        }
+
+        let expected_classes = self.class_histogram();
+        let saved_classes = saved.class_histogram();
+        for (class, expected) in &expected_classes {
+            let found = saved_classes.get(class).copied().unwrap_or_default();
+            if *expected != found {
+                problems.push(format!(
+                    "{} {} in memory but {} in the file",
+                    expected, class, found
+                ));
+            }
+        }
+        for (class, found) in &saved_classes {
+            if !expected_classes.contains_key(class) {
+                problems.push(format!("0 {} in memory but {} in the file", class, found));
+            }
+        }
+
+        let expected_references = reference_counts(&self.asset_inventory());
+        let saved_inventory = saved.asset_inventory();
+        let saved_references = reference_counts(&saved_inventory);
+        if expected_references != saved_references {
+            for (reference, expected) in &expected_references {
+                let found = saved_references.get(reference).copied().unwrap_or_default();
+                if *expected != found {
+                    problems.push(format!(
+                        "{} {} = {} differs from memory",
+                        reference.0, reference.1, reference.2
+                    ));
+                }
+            }
+            for reference in saved_references.keys() {
+                if !expected_references.contains_key(reference) {
+                    problems.push(format!(
+                        "{} {} = {} isn't in memory",
+                        reference.0, reference.1, reference.2
+                    ));
+                }
+            }
+        }
+
+        if let Some(animation_mapping) = animation_mapping {
+            for entry in &saved_inventory {
+                if !self.rewritten_ids.contains(&entry.asset_id)
+                    || !animation_mapping.contains_key(&entry.asset_id.to_string())
+                {
+                    continue;
+                }
+                if self.should_replace(entry) {
+                    problems.push(format!(
+                        "old ID {} remains at {} {}",
+                        entry.asset_id, entry.path, entry.property
+                    ));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            return Ok(());
+        }
+
+        let total = problems.len();
+        problems.truncate(MAX_LISTED);
+        let mut message = format!(
+            "Verification of '{}' failed:\n  {}",
+            file_path.display(),
+            problems.join("\n  ")
+        );
+        if total > MAX_LISTED {
+            message.push_str(&format!("\n  ...and {} more", total - MAX_LISTED));
+        }
+        Err(anyhow::anyhow!(message))
+    }
+
+    /// Number of instances of each class.
+    pub fn class_histogram(&self) -> BTreeMap<String, usize> {
+        let mut histogram = BTreeMap::new();
+        for instance in self.dom.descendants() {
+            *histogram.entry(instance.class.to_string()).or_default() += 1;
+        }
+        histogram
+    }
+
+    /// Whether the update functions would have replaced a mapped ID at this reference.
+    fn should_replace(&self, entry: &InventoryEntry) -> bool {
+        if !self.filter.allows_id(entry.asset_id) {
+            return false;
+        }
+        let skipped = self.skipped_values.iter().any(|skipped| {
+            skipped.asset_id == entry.asset_id
+                && skipped.path == entry.path
+                && skipped.property == entry.property
+        });
+        if skipped {
+            return false;
+        }
+        if entry.property == "Source" {
+            let name = entry.path.rsplit('/').next().unwrap_or_default();
+            return self.filter.allows_script(name, &entry.path);
+        }
+        self.filter.allows_path(&entry.path)
+    }
+}
+
+/// Counts each (path, property, asset ID) reference; several instances can share a path.
+fn reference_counts(entries: &[InventoryEntry]) -> HashMap<(String, String, u64), usize> {
+    let mut counts = HashMap::new();
+    for entry in entries {
+        *counts
+            .entry((entry.path.clone(), entry.property.clone(), entry.asset_id))
+            .or_default() += 1;
+    }
+    counts
+}
@@ -1,54 +1,37 @@
 use crate::StudioParser;
-use rbx_binary::to_writer;
-use rbx_types::Variant;
+use crate::asset::AssetType;
+use crate::progress::{ApplyTarget, ProgressEvent};
+use internal::{backup_path, sync_parent_dir, temp_path};
+use log::info;
 use std::collections::HashMap;
-use std::fs::File;
-use std::path::Path;
-use ustr::Ustr;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 impl StudioParser {
     /// Gets all animation instances in the file, scans their animationId then replaces them if a
-    /// new one is provided.
+    /// new one is provided. Value objects and attributes holding animation IDs are rewritten too.
+    /// Returns how many properties, values and attributes were rewritten.
     ///
-    pub fn update_game_animations(&mut self, animation_mapping: &HashMap<String, String>) {
-        let animation_instances_referent: Vec<_> = self
-            .dom
-            .descendants()
-            .filter(|instance| {
-                let instance_class_str = instance.class.as_str();
-                let is_animation = matches!(instance_class_str, "Animation");
-                is_animation
-            })
-            .map(|instance| instance.referent())
-            .collect();
-        // println!(
-        //     "Going through workplaces: {:?}",
-        //     animation_instances_referent.len()
-        // );
-
-        let animation_id_key = Ustr::from("AnimationId");
-        for animation_ref in animation_instances_referent {
-            if let Some(instance) = self.dom.get_by_ref_mut(animation_ref) {
-                if let Some(Variant::ContentId(content_id)) =
-                    instance.properties.get(&animation_id_key)
-                {
-                    let raw = content_id.as_str();
-                    let trimmed_id = raw.strip_prefix("rbxassetid://").unwrap_or(raw);
-                    if let Some(new_id) = animation_mapping.get(trimmed_id) {
-                        // Replace the AnimationId with the new one
-                        let rbxasset = format!("rbxassetid://{}", new_id);
-                        instance.properties.insert(
-                            animation_id_key,
-                            Variant::ContentId(rbxasset.clone().into()),
-                        );
-                    }
-                }
-            }
-        }
+    pub fn update_game_animations(&mut self, animation_mapping: &HashMap<String, String>) -> usize {
+        let updated = self.update_game_assets(AssetType::Animation, animation_mapping)
+            + self.update_value_animations(animation_mapping);
+        self.progress.emit(ProgressEvent::ApplyFinished {
+            target: ApplyTarget::Instances,
+            updated,
+        });
+        updated
     }
 
     /// Saves the DOM to a .rbxl file.
     ///
+    /// * Notes
+    /// The DOM is written to a temporary file next to the target, synced to disk, re-parsed
+    /// and checked against memory (see `verify_saved_file`), and only then renamed over the
+    /// target, so a crash or a bad write never replaces a good place. When the target is the
+    /// file the parser was loaded from, the original is first copied to
+    /// `<file>.<unix time>.bak`.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -58,13 +41,163 @@ impl StudioParser {
     /// parser.save_to_rbxl("output.rbxl")?;
     /// ```
     pub fn save_to_rbxl<P: AsRef<Path>>(&self, file_path: P) -> Result<(), anyhow::Error> {
+        self.save_verified(file_path, None)
+    }
+
+    /// Same as `save_to_rbxl`, also checking that every mapped ID was applied and no old ID
+    /// remains in the written file.
+    pub fn save_verified<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        animation_mapping: Option<&HashMap<String, String>>,
+    ) -> Result<(), anyhow::Error> {
         let expanded_path = shellexpand::full(file_path.as_ref().to_str().unwrap())?;
-        let file = File::create(expanded_path.as_ref())?;
+        let target = PathBuf::from(expanded_path.as_ref());
 
-        // Get the children of the root instead of the root
-        let root_children = self.dom.get_by_ref(self.dom.root_ref()).unwrap().children();
+        let temp_path = temp_path(&target)?;
+        let written = self
+            .write_synced(&temp_path)
+            .and_then(|_| self.verify_saved_file(&temp_path, animation_mapping));
+        if let Err(e) = written {
+            let _ = fs::remove_file(&temp_path);
+            return Err(anyhow::anyhow!(
+                "Not saving '{}', it was left untouched: {}",
+                target.display(),
+                e
+            ));
+        }
+
+        if self.is_source_file(&target) {
+            let backup = backup_path(&target)?;
+            if let Err(e) = fs::copy(&target, &backup) {
+                let _ = fs::remove_file(&temp_path);
+                return Err(anyhow::anyhow!(
+                    "Failed to back up '{}': {}",
+                    target.display(),
+                    e
+                ));
+            }
+            let backup_name = backup.display().to_string();
+            info!(backup = backup_name.as_str(); "Backed up {}", target.display());
+        }
+
+        if let Err(e) = fs::rename(&temp_path, &target) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(anyhow::anyhow!(
+                "Failed to replace '{}': {}",
+                target.display(),
+                e
+            ));
+        }
+        sync_parent_dir(&target);
 
-        to_writer(file, &self.dom, root_children)?;
         Ok(())
     }
 }
+
+/// Replaces `target` with `contents` the way `save_verified` replaces places: written to a
+/// temporary file next to it, synced, then renamed over it, so an interrupted write leaves
+/// the previous file intact.
+pub fn write_atomic(target: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    let temp_path = temp_path(target)?;
+    let written = fs::File::create(&temp_path).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp_path);
+        return Err(anyhow::anyhow!(
+            "Failed to write '{}': {}",
+            temp_path.display(),
+            e
+        ));
+    }
+
+    if let Err(e) = fs::rename(&temp_path, target) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(anyhow::anyhow!(
+            "Failed to replace '{}': {}",
+            target.display(),
+            e
+        ));
+    }
+    sync_parent_dir(target);
+    Ok(())
+}
+
+mod internal {
+    use crate::StudioParser;
+    use rbx_binary::to_writer;
+    use std::fs::{self, File};
+    use std::io::{BufWriter, Write};
+    use std::path::{Path, PathBuf};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    impl StudioParser {
+        /// Whether `target` is the file this parser was loaded from.
+        pub(super) fn is_source_file(&self, target: &Path) -> bool {
+            let Some(source) = &self.file_path else {
+                return false;
+            };
+            match (fs::canonicalize(source), fs::canonicalize(target)) {
+                (Ok(source), Ok(target)) => source == target,
+                _ => false,
+            }
+        }
+
+        /// Writes the DOM and waits until it is on disk.
+        pub(super) fn write_synced(&self, path: &Path) -> anyhow::Result<()> {
+            let file = File::create(path)
+                .map_err(|e| anyhow::anyhow!("Failed to create '{}': {}", path.display(), e))?;
+            let mut writer = BufWriter::new(file);
+
+            // Get the children of the root instead of the root
+            let root_children = self.dom.get_by_ref(self.dom.root_ref()).unwrap().children();
+
+            to_writer(&mut writer, &self.dom, root_children)?;
+            writer.flush()?;
+            writer
+                .into_inner()
+                .map_err(|e| anyhow::anyhow!("Failed to write '{}': {}", path.display(), e))?
+                .sync_all()?;
+            Ok(())
+        }
+    }
+
+    /// `Place.rbxl` -> `.Place.rbxl.<pid>.tmp` in the same directory, so the rename stays on
+    /// one filesystem.
+    pub(super) fn temp_path(target: &Path) -> anyhow::Result<PathBuf> {
+        let file_name = target
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("'{}' has no file name", target.display()))?;
+        Ok(target.with_file_name(format!(
+            ".{}.{}.tmp",
+            file_name.to_string_lossy(),
+            std::process::id()
+        )))
+    }
+
+    /// `Place.rbxl` -> `Place.rbxl.<unix time>.bak`.
+    pub(super) fn backup_path(target: &Path) -> anyhow::Result<PathBuf> {
+        let file_name = target
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("'{}' has no file name", target.display()))?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        Ok(target.with_file_name(format!("{}.{}.bak", file_name.to_string_lossy(), timestamp)))
+    }
+
+    /// Makes the rename itself durable. Not every platform can open a directory, so failures
+    /// are ignored.
+    pub(super) fn sync_parent_dir(target: &Path) {
+        let parent = match target.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+}